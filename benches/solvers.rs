@@ -0,0 +1,62 @@
+//! Benchmarks each day's generator and parts separately, over every puzzle example, named by its
+//! index, and over larger inputs from the `gen` module.
+//!
+//! Run with `cargo bench`, or `cargo bench -- day11` for a single day.
+
+use aoc_2021::gen;
+use aoc_2021::solver::{solvers, Solver};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+fn large_input(day: u8) -> String {
+    match day {
+        1 => gen::day1(1, 20_000),
+        // Any larger and part 2 overflows its `i32` position
+        2 => gen::day2(1, 1_000),
+        3 => gen::day3(1, 12, 2_000),
+        4 => gen::day4(1, 500),
+        5 => gen::day5(1, 2_000, 1_000),
+        6 => gen::day6(1, 100_000),
+        7 => gen::day7(1, 1_000, 2_000),
+        8 => gen::day8(1, 10_000),
+        9 => gen::day9(1, 500, 500),
+        10 => gen::day10(1, 5_000, 100, 25),
+        11 => gen::day11(1, 40, 6),
+        // Path count grows exponentially with the graph
+        12 => gen::day12(1, 6, 2, 4),
+        13 => gen::day13(1, 10_000, 12),
+        14 => gen::day14(1, 10, 10_000),
+        15 => gen::day15(1, 100, 100),
+        16 => gen::day16(1, 10),
+        x => panic!("No large input for day {}", x),
+    }
+}
+
+fn bench_solver(c: &mut Criterion, solver: &dyn Solver) {
+    let mut group = c.benchmark_group(format!("day{}", solver.day()));
+    let examples = solver.examples().iter().enumerate();
+    let inputs = examples
+        .map(|(idx, example)| (format!("example{}", idx), example.input.to_string()))
+        .chain([("large".to_string(), large_input(solver.day()))]);
+    for (name, input) in inputs {
+        group.bench_with_input(BenchmarkId::new("generator", &name), &input, |b, input| {
+            b.iter(|| solver.parse(black_box(input)))
+        });
+        let parsed = solver.parse(&input).expect("Invalid benchmark input");
+        group.bench_with_input(BenchmarkId::new("part1", &name), &parsed, |b, parsed| {
+            b.iter(|| solver.part1(black_box(parsed)))
+        });
+        group.bench_with_input(BenchmarkId::new("part2", name), &parsed, |b, parsed| {
+            b.iter(|| solver.part2(black_box(parsed)))
+        });
+    }
+    group.finish();
+}
+
+fn all_days(c: &mut Criterion) {
+    for solver in solvers() {
+        bench_solver(c, solver.as_ref());
+    }
+}
+
+criterion_group!(benches, all_days);
+criterion_main!(benches);
@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        // Errors are fine, panics are not
+        let _ = aoc_2021::runner::run_day(13, input, &[1, 2]);
+    }
+});
@@ -0,0 +1,224 @@
+//! Answers to the real inputs, stored in `answers.toml` next to the `dayN.txt` inputs so that
+//! refactors can be checked against them:
+//!
+//! ```toml
+//! [day1]
+//! part1 = "1292"
+//! part2 = "1262"
+//! ```
+//!
+//! Answers are recorded with `aoc2021 <DAY|all> --record` once they have been verified.
+
+use crate::runner::{self, DayResult};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use thiserror::Error;
+
+pub const FILE_NAME: &str = "answers.toml";
+
+#[derive(Debug, Error)]
+pub enum AnswersError {
+    #[error("Failed to access answers: {0}")]
+    Io(#[from] io::Error),
+    #[error("Invalid answers file: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[error("Invalid answers file: unexpected {0:?}, expected `[dayN]` tables of `partN` strings")]
+    Unexpected(String),
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Answers(BTreeMap<(u8, u8), String>);
+
+impl Answers {
+    /// Loads the answers stored at `path`, if any.
+    pub fn load(path: &Path) -> Result<Self, AnswersError> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => content.parse(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Answers::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), AnswersError> {
+        Ok(std::fs::write(path, self.to_string())?)
+    }
+
+    pub fn get(&self, day: u8, part: u8) -> Option<&str> {
+        self.0.get(&(day, part)).map(String::as_str)
+    }
+
+    /// Stores an answer, returning the one it replaces
+    pub fn set(&mut self, day: u8, part: u8, answer: String) -> Option<String> {
+        self.0.insert((day, part), answer)
+    }
+
+    /// Parts of `result` whose answer differs from the stored one.
+    /// Parts without a stored answer are not checked.
+    pub fn check<'a>(&self, result: &'a DayResult) -> Vec<Mismatch<'a>> {
+        result
+            .parts
+            .iter()
+            .filter_map(|part| {
+                let expected = self.get(result.day, part.part)?;
+                match &part.answer {
+                    Ok(answer) if answer == expected => None,
+                    found => Some(Mismatch {
+                        day: result.day,
+                        part: part.part,
+                        expected: expected.to_string(),
+                        found,
+                    }),
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub struct Mismatch<'a> {
+    pub day: u8,
+    pub part: u8,
+    pub expected: String,
+    pub found: &'a Result<String, crate::error::AocError>,
+}
+
+impl fmt::Display for Mismatch<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Day {} part {}: expected {:?}, ",
+            self.day, self.part, self.expected
+        )?;
+        match self.found {
+            Ok(answer) => write!(f, "found {:?}", answer),
+            Err(e) => write!(f, "failed with {}", e),
+        }
+    }
+}
+
+/// Parses a key such as `day12` or `part1`
+fn parse_key(key: &str, prefix: &str, range: std::ops::RangeInclusive<u8>) -> Option<u8> {
+    key.strip_prefix(prefix)?
+        .parse()
+        .ok()
+        .filter(|x| range.contains(x))
+}
+
+impl std::str::FromStr for Answers {
+    type Err = AnswersError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let table: toml::Table = input.parse()?;
+        let mut answers = Answers::default();
+        for (day_key, parts) in table {
+            let day = parse_key(&day_key, "day", runner::DAYS);
+            let (day, parts) = match (day, parts) {
+                (Some(day), toml::Value::Table(parts)) => (day, parts),
+                _ => return Err(AnswersError::Unexpected(day_key)),
+            };
+            for (part_key, answer) in parts {
+                match (parse_key(&part_key, "part", 1..=2), answer) {
+                    (Some(part), toml::Value::String(answer)) => answers.set(day, part, answer),
+                    _ => {
+                        return Err(AnswersError::Unexpected(format!(
+                            "{}.{}",
+                            day_key, part_key
+                        )))
+                    }
+                };
+            }
+        }
+        Ok(answers)
+    }
+}
+
+/// Writes days in order, unlike `toml` which would sort `day10` before `day2`
+impl fmt::Display for Answers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut last_day = None;
+        for (&(day, part), answer) in &self.0 {
+            if last_day != Some(day) {
+                if last_day.is_some() {
+                    writeln!(f)?;
+                }
+                writeln!(f, "[day{}]", day)?;
+                last_day = Some(day);
+            }
+            writeln!(f, "part{} = {}", part, toml::Value::String(answer.clone()))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::solver::solvers;
+    use std::path::PathBuf;
+
+    const ANSWERS: &str = "[day2]
+part1 = \"150\"
+
+[day10]
+part1 = \"26397\"
+part2 = \"288957\"
+";
+
+    #[test]
+    fn should_round_trip() -> Result<(), AnswersError> {
+        let answers: Answers = ANSWERS.parse()?;
+        assert_eq!(answers.get(10, 2), Some("288957"));
+        assert_eq!(answers.get(2, 2), None);
+        assert_eq!(answers.to_string(), ANSWERS);
+
+        let mut answers = Answers::default();
+        answers.set(13, 2, "\n█ █\n███".to_string());
+        assert_eq!(answers.to_string().parse::<Answers>()?, answers);
+        Ok(())
+    }
+
+    #[test]
+    fn should_reject_unknown_keys() {
+        assert!("[day26]\npart1 = \"1\"".parse::<Answers>().is_err());
+        assert!("[day1]\npart3 = \"1\"".parse::<Answers>().is_err());
+        assert!("[day1]\npart1 = 1".parse::<Answers>().is_err());
+    }
+
+    #[test]
+    fn should_report_mismatches() -> Result<(), Box<dyn std::error::Error>> {
+        let answers: Answers = ANSWERS.parse()?;
+        let result = runner::run_day(2, "forward 5\ndown 5", &[1, 2])?;
+        let mismatches = answers.check(&result);
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(
+            mismatches[0].to_string(),
+            "Day 2 part 1: expected \"150\", found \"25\""
+        );
+        Ok(())
+    }
+
+    /// Runs every day whose real input is in `input/2021` against the recorded answers
+    #[test]
+    fn should_match_recorded_answers() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(runner::INPUT_DIR);
+        let answers = Answers::load(&dir.join(FILE_NAME))?;
+        let mut mismatches = Vec::new();
+        for solver in solvers() {
+            let day = solver.day();
+            let input = match std::fs::read_to_string(runner::input_path(&dir, day)) {
+                Ok(input) => input,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    eprintln!("Day {}: no input, skipped", day);
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+            let result = solver.run(&input, &[1, 2])?;
+            mismatches.extend(answers.check(&result).iter().map(|m| m.to_string()));
+        }
+        assert!(mismatches.is_empty(), "{}", mismatches.join("\n"));
+        Ok(())
+    }
+}
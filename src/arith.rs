@@ -0,0 +1,137 @@
+//! Arithmetic of the answers that can outgrow their type on large inputs. With the
+//! `checked-arith` feature every operation is checked, an overflow being reported as
+//! `AocError::Overflow`. Without it, operations wrap around in debug builds as they do in release
+//! ones, so that large inputs give a wrong answer rather than a panic.
+
+use crate::error::AocError;
+
+/// Integers the answers are computed with
+pub trait Int: Copy {
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+    fn wrapping_add(self, other: Self) -> Self;
+    fn wrapping_sub(self, other: Self) -> Self;
+    fn wrapping_mul(self, other: Self) -> Self;
+}
+
+macro_rules! int {
+    ($($t:ty),*) => {
+        $(impl Int for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$t>::checked_add(self, other)
+            }
+
+            fn checked_sub(self, other: Self) -> Option<Self> {
+                <$t>::checked_sub(self, other)
+            }
+
+            fn checked_mul(self, other: Self) -> Option<Self> {
+                <$t>::checked_mul(self, other)
+            }
+
+            fn wrapping_add(self, other: Self) -> Self {
+                <$t>::wrapping_add(self, other)
+            }
+
+            fn wrapping_sub(self, other: Self) -> Self {
+                <$t>::wrapping_sub(self, other)
+            }
+
+            fn wrapping_mul(self, other: Self) -> Self {
+                <$t>::wrapping_mul(self, other)
+            }
+        })*
+    };
+}
+
+int!(i32, i64, u32, u64, usize);
+
+#[cfg(feature = "checked-arith")]
+fn apply<T>(
+    day: u8,
+    _wrapping: impl FnOnce() -> T,
+    checked: impl FnOnce() -> Option<T>,
+) -> Result<T, AocError> {
+    checked().ok_or(AocError::Overflow(day))
+}
+
+#[cfg(not(feature = "checked-arith"))]
+fn apply<T>(
+    _day: u8,
+    wrapping: impl FnOnce() -> T,
+    _checked: impl FnOnce() -> Option<T>,
+) -> Result<T, AocError> {
+    Ok(wrapping())
+}
+
+pub fn add<T: Int>(day: u8, a: T, b: T) -> Result<T, AocError> {
+    apply(day, || a.wrapping_add(b), || a.checked_add(b))
+}
+
+pub fn sub<T: Int>(day: u8, a: T, b: T) -> Result<T, AocError> {
+    apply(day, || a.wrapping_sub(b), || a.checked_sub(b))
+}
+
+pub fn mul<T: Int>(day: u8, a: T, b: T) -> Result<T, AocError> {
+    apply(day, || a.wrapping_mul(b), || a.checked_mul(b))
+}
+
+pub fn sum<T: Int>(day: u8, values: impl IntoIterator<Item = T>) -> Result<T, AocError> {
+    values
+        .into_iter()
+        .try_fold(T::ZERO, |total, x| add(day, total, x))
+}
+
+pub fn product<T: Int>(day: u8, values: impl IntoIterator<Item = T>) -> Result<T, AocError> {
+    values
+        .into_iter()
+        .try_fold(T::ONE, |total, x| mul(day, total, x))
+}
+
+/// `value` as an `i32`, for answers of that type computed in a larger one
+pub fn to_i32(day: u8, value: i128) -> Result<i32, AocError> {
+    apply(day, || value as i32, || i32::try_from(value).ok())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn should_compute() -> Result<(), AocError> {
+        assert_eq!(add(1, 2, 3)?, 5);
+        assert_eq!(sub(1, 2i32, 3)?, -1);
+        assert_eq!(sum(1, [1u64, 2, 3])?, 6);
+        assert_eq!(product(1, [2usize, 3, 4])?, 24);
+        assert_eq!(product(1, Vec::<u32>::new())?, 1);
+        assert_eq!(to_i32(7, -5)?, -5);
+        Ok(())
+    }
+
+    #[cfg(not(feature = "checked-arith"))]
+    #[test]
+    fn should_wrap_around() -> Result<(), AocError> {
+        assert_eq!(add(2, i32::MAX, 1)?, i32::MIN);
+        assert_eq!(sub(6, 0usize, 1)?, usize::MAX);
+        assert_eq!(mul(16, 1u64 << 63, 2)?, 0);
+        assert_eq!(to_i32(7, 1 << 31)?, i32::MIN);
+        Ok(())
+    }
+
+    #[cfg(feature = "checked-arith")]
+    #[test]
+    fn should_report_overflows() {
+        assert_eq!(add(2, i32::MAX, 1), Err(AocError::Overflow(2)));
+        assert_eq!(sub(6, 0usize, 1), Err(AocError::Overflow(6)));
+        assert_eq!(mul(7, 1 << 16, 1 << 16), Err(AocError::Overflow(7)));
+        assert_eq!(sum(16, [u64::MAX, 1]), Err(AocError::Overflow(16)));
+        assert_eq!(to_i32(7, 1 << 31), Err(AocError::Overflow(7)));
+    }
+}
@@ -0,0 +1,634 @@
+//! Runs the solvers without `cargo aoc`.
+//!
+//! ```text
+//! aoc2021 <DAY|all> [--part 1|2] [--input PATH] [--record] [--format text|json|csv]
+//!                    [--jobs N] [--timeout SECS] [--variant NAME] [--cache DIR]
+//! aoc2021 <DAY|all> --verify [--part 1|2] [--input PATH] [--timeout SECS]
+//! aoc2021 <DAY|all> --lint [--input PATH]
+//! aoc2021 <DAY> --viz DIR [--input PATH]
+//! aoc2021 <DAY> --stream [--part 1|2] [--input PATH]
+//! ```
+//!
+//! `PATH` is a file for a single day (`-` reads stdin) and a directory holding `dayN.txt` files
+//! when running all days. It defaults to `input/2021`, the layout used by `cargo aoc`.
+//!
+//! Answers are checked against the ones stored in `answers.toml`, in the directory of the
+//! inputs. `--record` stores the answers of this run there instead, once they're verified.
+//!
+//! Days run concurrently on `--jobs` threads (one per core by default). Parsing and each part are
+//! given `--timeout` seconds (60 by default), after which they're reported as timed out.
+//!
+//! `--variant` solves the parts with another implementation than the default one, such as the
+//! `naive` one of the `oracle` module. `--verify` solves them with every implementation, one
+//! after the other, and fails if they don't all give the same answer.
+//!
+//! `--lint` checks the inputs against the format of their day without solving them, and prints
+//! every problem found along with its line and column.
+//!
+//! `--format json` and `--format csv` print a report of the answers, timings and errors meant
+//! for scripts instead of the text output, see the `report` module.
+//!
+//! `--viz` writes the frames of the days that can be visualized to `DIR` as images, or prints
+//! them as ASCII art if `DIR` is `-`.
+//!
+//! Built with the `cache` feature, `--cache` stores the parsed inputs in `DIR` and reuses them
+//! on later runs of the same inputs, see the `cache` module.
+//!
+//! Built with the `tracing` feature, the spans and events of the solvers are printed to stderr.
+//!
+//! `--stream` solves the days of the `stream` module while reading their input, for inputs too
+//! large to be loaded in memory.
+
+use aoc_2021::answers::{self, Answers};
+#[cfg(feature = "cache")]
+use aoc_2021::cache::Cache;
+use aoc_2021::lint;
+use aoc_2021::memory::AllocStats;
+use aoc_2021::report::Report;
+use aoc_2021::runner::{self, DayResult, RunOptions};
+use aoc_2021::stream;
+use aoc_2021::viz::{self, Format, Frame};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+const USAGE: &str =
+    "Usage: aoc2021 <DAY|all> [--part 1|2] [--input PATH] [--record] [--format text|json|csv]
+                          [--jobs N] [--timeout SECS] [--variant NAME] [--cache DIR]
+       aoc2021 <DAY|all> --verify [--part 1|2] [--input PATH] [--timeout SECS]
+       aoc2021 <DAY|all> --lint [--input PATH]
+       aoc2021 <DAY> --viz DIR [--input PATH]
+       aoc2021 <DAY> --stream [--part 1|2] [--input PATH]";
+
+/// Images are scaled up to about this size
+const VIZ_SIZE: usize = 500;
+
+enum Days {
+    One(u8),
+    All,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Output {
+    Text,
+    Json,
+    Csv,
+}
+
+struct Args {
+    days: Days,
+    parts: Vec<u8>,
+    input: Option<PathBuf>,
+    record: bool,
+    viz: Option<PathBuf>,
+    stream: bool,
+    verify: bool,
+    lint: bool,
+    output: Output,
+    options: RunOptions,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let days = match args.next().as_deref() {
+        Some("all") => Days::All,
+        Some(x) => match x.parse() {
+            Ok(day) if runner::DAYS.contains(&day) => Days::One(day),
+            _ => return Err(format!("Invalid day {:?}", x)),
+        },
+        None => return Err("Missing day".to_string()),
+    };
+    let mut parts = vec![1, 2];
+    let mut input = None;
+    let mut record = false;
+    let mut viz = None;
+    let mut stream = false;
+    let mut verify = false;
+    let mut lint = false;
+    let mut output = Output::Text;
+    let mut options = RunOptions::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--part" => match args.next().as_deref() {
+                Some("1") => parts = vec![1],
+                Some("2") => parts = vec![2],
+                x => return Err(format!("Invalid part {:?}", x)),
+            },
+            "--input" => match args.next() {
+                Some(path) => input = Some(PathBuf::from(path)),
+                None => return Err("Missing input path".to_string()),
+            },
+            "--record" => record = true,
+            "--stream" => stream = true,
+            "--verify" => verify = true,
+            "--lint" => lint = true,
+            "--variant" => match args.next() {
+                Some(name) => options.variant = name,
+                None => return Err("Missing variant name".to_string()),
+            },
+            "--format" => match args.next().as_deref() {
+                Some("text") => output = Output::Text,
+                Some("json") => output = Output::Json,
+                Some("csv") => output = Output::Csv,
+                x => return Err(format!("Invalid format {:?}", x)),
+            },
+            "--jobs" => match args.next().map(|x| x.parse()) {
+                Some(Ok(jobs)) if jobs > 0 => options.threads = jobs,
+                _ => return Err("Invalid number of jobs".to_string()),
+            },
+            "--timeout" => match args
+                .next()
+                .map(|x| x.parse().map(Duration::try_from_secs_f64))
+            {
+                Some(Ok(Ok(timeout))) if !timeout.is_zero() => options.timeout = timeout,
+                _ => return Err("Invalid timeout".to_string()),
+            },
+            #[cfg(feature = "cache")]
+            "--cache" => match args.next() {
+                Some(dir) => options.cache = Some(Cache::new(dir)),
+                None => return Err("Missing cache directory".to_string()),
+            },
+            "--viz" => match args.next() {
+                Some(path) => viz = Some(PathBuf::from(path)),
+                None => return Err("Missing visualization directory".to_string()),
+            },
+            x => return Err(format!("Unknown argument {:?}", x)),
+        }
+    }
+    if viz.is_some() && matches!(days, Days::All) {
+        return Err("Only a single day can be visualized".to_string());
+    }
+    if stream && matches!(days, Days::All) {
+        return Err("Only a single day can be streamed".to_string());
+    }
+    let other_mode = record || viz.is_some() || stream || output != Output::Text;
+    let timeout_only = RunOptions {
+        timeout: options.timeout,
+        ..RunOptions::default()
+    };
+    if verify && (other_mode || options != timeout_only) {
+        return Err("--verify only goes with --part, --input and --timeout".to_string());
+    }
+    if lint && (other_mode || verify || parts.len() != 2 || options != RunOptions::default()) {
+        return Err("--lint only goes with --input".to_string());
+    }
+    Ok(Args {
+        days,
+        parts,
+        input,
+        record,
+        viz,
+        stream,
+        verify,
+        lint,
+        output,
+        options,
+    })
+}
+
+fn read_input(path: &Path) -> Result<String, String> {
+    let mut input = String::new();
+    if path == Path::new("-") {
+        std::io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|e| format!("Failed to read stdin: {}", e))?;
+    } else {
+        input = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    }
+    Ok(input)
+}
+
+/// Prints the results of a day, returns whether every part was solved
+fn report(result: &DayResult) -> bool {
+    // Allocations are only counted with the `count-allocs` feature
+    let allocs = |stats: Option<AllocStats>| stats.map(|s| format!(", {}", s)).unwrap_or_default();
+    println!(
+        "Day {} - parsed in {:?}{}",
+        result.day,
+        result.parse_time,
+        allocs(result.parse_allocs)
+    );
+    let mut ok = true;
+    for part in &result.parts {
+        match &part.answer {
+            Ok(answer) => println!(
+                "  part {}: {} ({:?}{})",
+                part.part,
+                answer,
+                part.time,
+                allocs(part.allocs)
+            ),
+            Err(e) => {
+                ok = false;
+                eprintln!("  part {}: {}", part.part, e)
+            }
+        }
+    }
+    ok
+}
+
+/// Prints the answers differing from the stored ones, returns whether there were none
+fn check(result: &DayResult, answers: &Answers) -> bool {
+    let mismatches = answers.check(result);
+    for mismatch in &mismatches {
+        eprintln!("  {}", mismatch);
+    }
+    mismatches.is_empty()
+}
+
+/// Reads the input of each day then runs them concurrently, results being in the same order
+fn run_days(
+    paths: &[(u8, PathBuf)],
+    parts: &[u8],
+    options: RunOptions,
+) -> Vec<(u8, Result<DayResult, String>)> {
+    let mut inputs: Vec<(u8, Result<String, String>)> = paths
+        .iter()
+        .map(|(day, path)| (*day, read_input(path)))
+        .collect();
+    let readable = inputs
+        .iter_mut()
+        .filter_map(|(day, input)| Some((*day, std::mem::take(input.as_mut().ok()?))))
+        .collect();
+    let mut results = runner::run_all(readable, parts, options).into_iter();
+    inputs
+        .into_iter()
+        .map(|(day, input)| match input {
+            Ok(_) => {
+                let (day, result) = results.next().expect("Every readable input is run");
+                (day, result.map_err(|e| e.to_string()))
+            }
+            Err(e) => (day, Err(e)),
+        })
+        .collect()
+}
+
+/// Renders the frames of `day`, returns whether it went well
+fn visualize(day: u8, path: &Path, out: &Path) -> bool {
+    let visualizer = match viz::visualizer(day) {
+        Some(visualizer) => visualizer,
+        None => {
+            eprintln!("Day {} has no visualization", day);
+            return false;
+        }
+    };
+    let frames =
+        match read_input(path).and_then(|input| visualizer(&input).map_err(|e| e.to_string())) {
+            Ok(frames) => frames,
+            Err(e) => {
+                eprintln!("{}", e);
+                return false;
+            }
+        };
+    if out == Path::new("-") {
+        for frame in frames {
+            println!("{}\n", frame.to_ascii());
+        }
+        return true;
+    }
+    let scale = |frame: &Frame| (VIZ_SIZE / frame.width().max(frame.height()).max(1)).max(1);
+    let frames: Vec<Frame> = frames.iter().map(|f| f.scaled(scale(f))).collect();
+    match viz::write_frames(&frames, out, Format::Pnm) {
+        Ok(()) => {
+            println!("{} frames written to {}", frames.len(), out.display());
+            true
+        }
+        Err(e) => {
+            eprintln!("Failed to write frames: {}", e);
+            false
+        }
+    }
+}
+
+/// Solves `day` while reading its input, returns whether every part was solved
+fn stream(day: u8, path: &Path, parts: &[u8]) -> bool {
+    let streamer = match stream::streamer(day) {
+        Some(streamer) => streamer,
+        None => {
+            eprintln!("Day {} can't be streamed", day);
+            return false;
+        }
+    };
+    let start = Instant::now();
+    let answers = if path == Path::new("-") {
+        streamer(&mut std::io::stdin().lock())
+    } else {
+        match File::open(path) {
+            Ok(file) => streamer(&mut BufReader::new(file)),
+            Err(e) => {
+                eprintln!("Failed to read {}: {}", path.display(), e);
+                return false;
+            }
+        }
+    };
+    let answers = match answers {
+        Ok(answers) => answers,
+        Err(e) => {
+            eprintln!("{}", e);
+            return false;
+        }
+    };
+    println!("Day {} - streamed in {:?}", day, start.elapsed());
+    let mut ok = true;
+    for (part, answer) in (1..).zip(answers) {
+        match answer {
+            _ if !parts.contains(&part) => (),
+            Ok(answer) => println!("  part {}: {}", part, answer),
+            Err(e) => {
+                ok = false;
+                eprintln!("  part {}: {}", part, e)
+            }
+        }
+    }
+    ok
+}
+
+/// Solves the parts of each day with all their variants, returns whether they all agree
+fn verify(paths: &[(u8, PathBuf)], parts: &[u8], timeout: Duration) -> bool {
+    let mut ok = true;
+    for (day, path) in paths {
+        let verification = read_input(path).and_then(|input| {
+            runner::verify(*day, input, parts, timeout).map_err(|e| e.to_string())
+        });
+        let verification = match verification {
+            Ok(verification) => verification,
+            Err(e) => {
+                eprintln!("{}", e);
+                ok = false;
+                continue;
+            }
+        };
+        println!("Day {} - parsed in {:?}", day, verification.parse_time);
+        let disagreements = verification.disagreements();
+        for (part, results) in parts.iter().zip(&verification.parts) {
+            if disagreements.contains(part) {
+                ok = false;
+                println!("  part {}: variants disagree", part);
+            } else {
+                println!("  part {}:", part);
+            }
+            for (variant, result) in results {
+                match &result.answer {
+                    Ok(answer) => println!("    {}: {} ({:?})", variant, answer, result.time),
+                    Err(e) => {
+                        ok = false;
+                        eprintln!("    {}: {}", variant, e)
+                    }
+                }
+            }
+        }
+    }
+    ok
+}
+
+/// Prints the problems found in the input of each day, returns whether there are none
+fn lint(paths: &[(u8, PathBuf)]) -> bool {
+    let mut ok = true;
+    for (day, path) in paths {
+        let errors =
+            read_input(path).and_then(|input| lint::lint(*day, &input).map_err(|e| e.to_string()));
+        match errors {
+            Ok(errors) if errors.is_empty() => println!("Day {} - input is well formed", day),
+            Ok(errors) => {
+                ok = false;
+                println!("Day {} - {} problem(s) found", day, errors.len());
+                for e in errors {
+                    eprintln!("  {}", e);
+                }
+            }
+            Err(e) => {
+                ok = false;
+                eprintln!("{}", e);
+            }
+        }
+    }
+    ok
+}
+
+/// Stores the answers of every solved part, returns whether they could be saved
+fn record(results: &[(u8, Result<DayResult, String>)], answers: &mut Answers, path: &Path) -> bool {
+    for result in results.iter().filter_map(|(_, r)| r.as_ref().ok()) {
+        for part in &result.parts {
+            if let Ok(answer) = &part.answer {
+                match answers.set(result.day, part.part, answer.clone()) {
+                    Some(previous) if &previous != answer => eprintln!(
+                        "Day {} part {}: replaced {:?} with {:?}",
+                        result.day, part.part, previous, answer
+                    ),
+                    _ => (),
+                }
+            }
+        }
+    }
+    match answers.save(path) {
+        Ok(()) => {
+            eprintln!("Answers recorded in {}", path.display());
+            true
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            false
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    #[cfg(feature = "tracing")]
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_writer(std::io::stderr)
+        .init();
+
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    if let Days::One(day) = args.days {
+        let path = args
+            .input
+            .clone()
+            .unwrap_or_else(|| runner::input_path(Path::new(runner::INPUT_DIR), day));
+        let ok = match &args.viz {
+            Some(out) => Some(visualize(day, &path, out)),
+            None if args.stream => Some(stream(day, &path, &args.parts)),
+            None => None,
+        };
+        match ok {
+            Some(true) => return ExitCode::SUCCESS,
+            Some(false) => return ExitCode::FAILURE,
+            None => (),
+        }
+    }
+
+    if args.verify || args.lint {
+        let paths: Vec<(u8, PathBuf)> = match args.days {
+            Days::One(day) => {
+                let path = args
+                    .input
+                    .unwrap_or_else(|| runner::input_path(Path::new(runner::INPUT_DIR), day));
+                vec![(day, path)]
+            }
+            Days::All => {
+                let dir = args
+                    .input
+                    .unwrap_or_else(|| PathBuf::from(runner::INPUT_DIR));
+                runner::DAYS
+                    .map(|day| (day, runner::input_path(&dir, day)))
+                    .collect()
+            }
+        };
+        let ok = if args.lint {
+            lint(&paths)
+        } else {
+            verify(&paths, &args.parts, args.options.timeout)
+        };
+        return match ok {
+            true => ExitCode::SUCCESS,
+            false => ExitCode::FAILURE,
+        };
+    }
+
+    // Answers live next to the inputs, or in the default directory when reading stdin
+    let (dir, results) = match args.days {
+        Days::One(day) => {
+            let default_dir = Path::new(runner::INPUT_DIR);
+            let path = args
+                .input
+                .unwrap_or_else(|| runner::input_path(default_dir, day));
+            let dir = match path.parent() {
+                Some(dir) if path != Path::new("-") => dir.to_path_buf(),
+                _ => default_dir.to_path_buf(),
+            };
+            (dir, run_days(&[(day, path)], &args.parts, args.options))
+        }
+        Days::All => {
+            let dir = args
+                .input
+                .unwrap_or_else(|| PathBuf::from(runner::INPUT_DIR));
+            // Run everything first so that a single broken day doesn't hide the others
+            let paths: Vec<(u8, PathBuf)> = runner::DAYS
+                .map(|day| (day, runner::input_path(&dir, day)))
+                .collect();
+            let results = run_days(&paths, &args.parts, args.options);
+            (dir, results)
+        }
+    };
+
+    let answers_path = dir.join(answers::FILE_NAME);
+    let mut answers = match Answers::load(&answers_path) {
+        Ok(answers) => answers,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    // When recording, the stored answers are the ones being replaced
+    let expected = if args.record {
+        Answers::default()
+    } else {
+        answers.clone()
+    };
+    let mut ok = true;
+    for (_, result) in &results {
+        match result {
+            Ok(result) => {
+                if args.output == Output::Text {
+                    ok &= report(result);
+                }
+                ok &= check(result, &expected);
+            }
+            Err(e) => {
+                if args.output == Output::Text {
+                    eprintln!("{}", e);
+                }
+                ok = false;
+            }
+        }
+    }
+    match args.output {
+        Output::Text => (),
+        Output::Json => println!("{}", Report::new(&results, &expected).to_json()),
+        Output::Csv => print!("{}", Report::new(&results, &expected).to_csv()),
+    }
+    if args.record {
+        ok &= record(&results, &mut answers, &answers_path);
+    }
+
+    if ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use aoc_2021::solver::DEFAULT_VARIANT;
+
+    fn args(args: &[&str]) -> Result<Args, String> {
+        parse_args(args.iter().map(|x| x.to_string()))
+    }
+
+    #[test]
+    fn should_parse_args() -> Result<(), String> {
+        let parsed = args(&["5", "--part", "2", "--input", "-"])?;
+        assert!(matches!(parsed.days, Days::One(5)));
+        assert_eq!(parsed.parts, vec![2]);
+        assert_eq!(parsed.input, Some(PathBuf::from("-")));
+
+        let parsed = args(&["all"])?;
+        assert!(matches!(parsed.days, Days::All));
+        assert_eq!(parsed.parts, vec![1, 2]);
+        assert!(!parsed.record);
+
+        assert!(args(&["all", "--record"])?.record);
+        assert_eq!(args(&["9", "--viz", "-"])?.viz, Some(PathBuf::from("-")));
+        assert!(args(&["10", "--stream"])?.stream);
+        assert_eq!(args(&["all", "--format", "csv"])?.output, Output::Csv);
+        let options = args(&["all", "--jobs", "3", "--timeout", "0.5"])?.options;
+        assert_eq!(options.threads, 3);
+        assert_eq!(options.timeout, Duration::from_millis(500));
+        assert_eq!(options.variant, DEFAULT_VARIANT);
+        assert_eq!(
+            args(&["7", "--variant", "median"])?.options.variant,
+            "median"
+        );
+        assert!(args(&["all", "--verify", "--part", "1"])?.verify);
+        assert!(args(&["4", "--lint", "--input", "-"])?.lint);
+        #[cfg(feature = "cache")]
+        assert_eq!(
+            args(&["all", "--cache", "target/cache"])?.options.cache,
+            Some(Cache::new("target/cache"))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn should_reject_bad_args() {
+        assert!(args(&[]).is_err());
+        assert!(args(&["17"]).is_err());
+        assert!(args(&["3", "--part", "3"]).is_err());
+        assert!(args(&["3", "--input"]).is_err());
+        assert!(args(&["all", "--viz", "out"]).is_err());
+        assert!(args(&["all", "--stream"]).is_err());
+        assert!(args(&["all", "--format", "xml"]).is_err());
+        assert!(args(&["all", "--jobs", "0"]).is_err());
+        assert!(args(&["all", "--timeout", "-1"]).is_err());
+        assert!(args(&["7", "--variant"]).is_err());
+        assert!(args(&["7", "--verify", "--variant", "naive"]).is_err());
+        assert!(args(&["7", "--verify", "--record"]).is_err());
+        assert!(args(&["7", "--verify", "--jobs", "1000"]).is_err());
+        #[cfg(feature = "cache")]
+        assert!(args(&["7", "--verify", "--cache", "target/cache"]).is_err());
+        assert!(args(&["all", "--lint", "--part", "1"]).is_err());
+        assert!(args(&["all", "--lint", "--verify"]).is_err());
+        assert!(args(&["all", "--lint", "--timeout", "5"]).is_err());
+    }
+}
@@ -0,0 +1,149 @@
+//! Parsed inputs stored on disk, so that later runs on the same input skip parsing it, which is
+//! most of the work for some days such as the hex expansion of day 16 or the large grids.
+//!
+//! Each day has a single file holding a header, with the version of the file format, the version
+//! of the crate, the parsed type and the hash of the normalized input, then the generator output
+//! serialized with `bincode`. An entry whose header doesn't match is ignored and replaced once the
+//! input is parsed again, so changing any of them invalidates it.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Version of the layout of the files, to bump whenever it changes
+const FORMAT_VERSION: u32 = 1;
+
+/// Entries written by another version of the crate are ignored, as the parsed types may differ
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Header {
+    format: u32,
+    version: String,
+    /// Name of the parsed type, so that loading an entry as another type misses
+    type_name: String,
+    hash: u64,
+}
+
+impl Header {
+    fn new<T>(input: &str) -> Self {
+        Header {
+            format: FORMAT_VERSION,
+            version: VERSION.to_string(),
+            type_name: std::any::type_name::<T>().to_string(),
+            hash: hash(input),
+        }
+    }
+}
+
+/// FNV-1a, as `std`'s hashers aren't guaranteed to give the same hash from one release to the
+/// next
+fn hash(input: &str) -> u64 {
+    input.bytes().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Directory holding the parsed inputs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Cache { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, day: u8) -> PathBuf {
+        self.dir.join(format!("day{}.bin", day))
+    }
+
+    /// Parsed `input` of `day`, if it was stored for this very input by this version of the crate.
+    /// Missing, stale and unreadable entries all give `None`.
+    pub fn load<T: DeserializeOwned>(&self, day: u8, input: &str) -> Option<T> {
+        let bytes = fs::read(self.path(day)).ok()?;
+        let mut reader = bytes.as_slice();
+        let header: Header = bincode::deserialize_from(&mut reader).ok()?;
+        if header != Header::new::<T>(input) {
+            return None;
+        }
+        bincode::deserialize(reader).ok()
+    }
+
+    /// Stores `parsed` as the parsed `input` of `day`, replacing whatever was stored before
+    pub fn store<T: Serialize>(&self, day: u8, input: &str, parsed: &T) -> io::Result<()> {
+        let bytes = bincode::serialize(&(Header::new::<T>(input), parsed))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.path(day), bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AocError;
+    use crate::grid::Grid;
+
+    fn cache(name: &str) -> Cache {
+        let dir =
+            std::env::temp_dir().join(format!("aoc-2021-cache-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        Cache::new(dir)
+    }
+
+    #[test]
+    fn should_reuse_parsed_inputs() -> Result<(), AocError> {
+        let cache = cache("reuse");
+        let input = include_str!("../examples/day15/example.txt");
+        assert_eq!(cache.load::<Grid<u8>>(15, input), None);
+
+        let parsed = crate::day15::parse(input)?;
+        cache.store(15, input, &parsed).expect("Failed to store");
+        assert_eq!(cache.load(15, input), Some(parsed));
+        // Another input, or another day with the same input, doesn't hit
+        assert_eq!(cache.load::<Grid<u8>>(15, &input[1..]), None);
+        assert_eq!(cache.load::<Grid<u8>>(9, input), None);
+        // Nor does the same input parsed as another type
+        assert_eq!(cache.load::<Grid<u16>>(15, input), None);
+
+        let _ = fs::remove_dir_all(cache.dir());
+        Ok(())
+    }
+
+    #[test]
+    fn should_ignore_stale_entries() {
+        let cache = cache("stale");
+        cache
+            .store(7, "1,2,3", &vec![1, 2, 3])
+            .expect("Failed to store");
+        assert_eq!(cache.load(7, "1,2,3"), Some(vec![1, 2, 3]));
+        let current = Header::new::<Vec<i32>>("1,2,3");
+        let stale = [
+            Header {
+                version: "0.0.0".to_string(),
+                ..current.clone()
+            },
+            Header {
+                format: FORMAT_VERSION + 1,
+                ..current
+            },
+        ];
+        for header in stale {
+            let bytes = bincode::serialize(&(header, vec![1, 2, 3])).expect("Failed to serialize");
+            fs::write(cache.path(7), bytes).expect("Failed to write");
+            assert_eq!(cache.load::<Vec<i32>>(7, "1,2,3"), None);
+        }
+
+        fs::write(cache.path(7), b"garbage").expect("Failed to write");
+        assert_eq!(cache.load::<Vec<i32>>(7, "1,2,3"), None);
+        let _ = fs::remove_dir_all(cache.dir());
+    }
+}
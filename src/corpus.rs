@@ -0,0 +1,170 @@
+//! Examples and regression cases stored as files, `examples/dayN/<name>.txt`, with the answers
+//! they're expected to give listed in `examples/dayN/expected.toml`:
+//!
+//! ```toml
+//! [example]
+//! part1 = "7"
+//! part2 = "5"
+//! ```
+//!
+//! Parts without an expected answer are only checked to be solved without error, so adding a
+//! regression case is just dropping a file in, and its answers once they are known.
+
+use crate::answers::{Answers, AnswersError};
+use crate::runner;
+use std::fmt;
+use std::path::Path;
+
+pub const EXAMPLES_DIR: &str = "examples";
+
+pub const EXPECTED_FILE: &str = "expected.toml";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Case {
+    pub day: u8,
+    /// Name of the file, without its `.txt` extension
+    pub name: String,
+    pub input: String,
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+impl Case {
+    /// Expected answers, to be checked with `Answers::check`
+    pub fn answers(&self) -> Answers {
+        let mut answers = Answers::default();
+        for (part, answer) in [(1, &self.part1), (2, &self.part2)] {
+            if let Some(answer) = answer {
+                answers.set(self.day, part, answer.clone());
+            }
+        }
+        answers
+    }
+}
+
+impl fmt::Display for Case {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "day{}/{}", self.day, self.name)
+    }
+}
+
+/// Expected answers of the cases of a day, by name
+fn load_expected(path: &Path) -> Result<toml::Table, AnswersError> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(content.parse()?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(toml::Table::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Expected answer of `part` in the entry of a case
+fn expected_answer(entry: &toml::Table, part: &str) -> Result<Option<String>, AnswersError> {
+    match entry.get(part) {
+        Some(toml::Value::String(answer)) => Ok(Some(answer.clone())),
+        Some(_) => Err(AnswersError::Unexpected(part.to_string())),
+        None => Ok(None),
+    }
+}
+
+/// Loads the cases of a `dayN` directory, sorted by name
+fn load_day(day: u8, dir: &Path) -> Result<Vec<Case>, AnswersError> {
+    let mut expected = load_expected(&dir.join(EXPECTED_FILE))?;
+    let mut cases = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = match (path.file_stem(), path.extension()) {
+            (Some(name), Some(ext)) if ext == "txt" => name.to_string_lossy().into_owned(),
+            _ => continue,
+        };
+        let entry = match expected.remove(&name) {
+            Some(toml::Value::Table(entry)) => entry,
+            Some(_) => return Err(AnswersError::Unexpected(format!("day{}.{}", day, name))),
+            None => toml::Table::new(),
+        };
+        if let Some(key) = entry.keys().find(|&key| key != "part1" && key != "part2") {
+            return Err(AnswersError::Unexpected(format!("{}.{}", name, key)));
+        }
+        cases.push(Case {
+            day,
+            part1: expected_answer(&entry, "part1")?,
+            part2: expected_answer(&entry, "part2")?,
+            input: std::fs::read_to_string(&path)?,
+            name,
+        });
+    }
+    // Answers of a file that doesn't exist, most likely a typo
+    if let Some(name) = expected.keys().next() {
+        return Err(AnswersError::Unexpected(format!("day{}.{}", day, name)));
+    }
+    cases.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(cases)
+}
+
+/// Loads the cases of every day in `dir`, sorted by day then name
+pub fn load(dir: &Path) -> Result<Vec<Case>, AnswersError> {
+    let mut cases = Vec::new();
+    for day in runner::DAYS {
+        let day_dir = dir.join(format!("day{}", day));
+        if day_dir.is_dir() {
+            cases.extend(load_day(day, &day_dir)?);
+        }
+    }
+    Ok(cases)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Runs every case of the `examples` directory
+    #[test]
+    fn should_solve_corpus() -> Result<(), AnswersError> {
+        let cases = load(&PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(EXAMPLES_DIR))?;
+        for day in runner::DAYS {
+            assert!(
+                cases.iter().any(|c| c.day == day),
+                "Day {} has no case",
+                day
+            );
+        }
+
+        let mut failures = Vec::new();
+        for case in &cases {
+            let result = match runner::run_day(case.day, &case.input, &[1, 2]) {
+                Ok(result) => result,
+                Err(e) => {
+                    failures.push(format!("{}: {}", case, e));
+                    continue;
+                }
+            };
+            let answers = case.answers();
+            let mismatches = answers.check(&result);
+            failures.extend(mismatches.iter().map(|m| format!("{}: {}", case, m)));
+            for part in &result.parts {
+                if let (Err(e), None) = (&part.answer, answers.get(case.day, part.part)) {
+                    failures.push(format!("{} part {}: {}", case, part.part, e));
+                }
+            }
+        }
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn should_reject_answers_without_file() -> Result<(), AnswersError> {
+        let dir = std::env::temp_dir().join(format!("aoc-2021-corpus-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("day7"))?;
+        std::fs::write(dir.join("day7/small.txt"), "1,2,3\n")?;
+        std::fs::write(dir.join("day7/expected.toml"), "[small]\npart1 = \"2\"\n")?;
+        let cases = load(&dir)?;
+        assert_eq!(cases.len(), 1);
+        assert_eq!(cases[0].to_string(), "day7/small");
+        assert_eq!(cases[0].answers().get(7, 1), Some("2"));
+
+        std::fs::write(dir.join("day7/expected.toml"), "[smal]\npart1 = \"2\"\n")?;
+        assert!(load(&dir).is_err());
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
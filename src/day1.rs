@@ -1,41 +1,98 @@
-use std::num::ParseIntError;
+use crate::error::{lint_lines, parse_field, AocError};
+use crate::input::normalize;
+use crate::oracle;
+use crate::solver::{Example, Solution, Solver, Variant};
+use crate::stream::for_each_line;
+use std::io::BufRead;
 
+/// Parses one depth per line
 #[aoc_generator(day1)]
-fn ints(input: &str) -> Result<Vec<u32>, ParseIntError> {
-    input.lines().map(|dep| dep.parse()).collect()
+pub fn ints(input: &str) -> Result<Vec<u32>, AocError> {
+    let input = &normalize(input);
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, dep)| parse_field(1, dep, dep).map_err(|e| e.at_line(idx + 1)))
+        .collect()
+}
+
+/// Every line that isn't a depth
+pub fn lint(input: &str) -> Vec<AocError> {
+    lint_lines(input, |l| parse_field::<u32>(1, l, l))
+}
+
+/// Number of times the sum of `window` consecutive depths increases.
+/// Consecutive windows share all but one depth, so only the ones going in and out are compared.
+pub fn increases(depths: &[u32], window: usize) -> usize {
+    depths
+        .iter()
+        .zip(depths.iter().skip(window))
+        .filter(|(out, new)| out < new)
+        .count()
 }
 
 #[aoc(day1, part1)]
 pub fn part1(depths: &[u32]) -> usize {
-    depths.windows(2).filter(|w| w[0] < w[1]).count()
+    increases(depths, 1)
 }
 
 #[aoc(day1, part2)]
 pub fn part2(depths: &[u32]) -> usize {
-    let depth_windows: Vec<u32> = depths.windows(3).map(|w| w.iter().sum()).collect();
-    depth_windows.windows(2).filter(|w| w[0] < w[1]).count()
+    increases(depths, 3)
 }
 
-#[cfg(test)]
-pub mod tests {
+/// Both parts in a single pass, only keeping the last three depths
+pub fn stream(input: impl BufRead) -> Result<(usize, usize), AocError> {
+    let mut last = [0; 3];
+    let mut seen = 0;
+    let (mut increases, mut window_increases) = (0, 0);
+    for_each_line(1, input, |line| {
+        let depth = parse_field(1, line, line)?;
+        // Same as `increases`, with the depths that went in the window
+        if seen >= 1 && last[2] < depth {
+            increases += 1;
+        }
+        if seen >= 3 && last[0] < depth {
+            window_increases += 1;
+        }
+        last = [last[1], last[2], depth];
+        seen += 1;
+        Ok(())
+    })?;
+    Ok((increases, window_increases))
+}
+
+const EXAMPLE: &str = include_str!("../examples/day1/example.txt");
 
-    const INPUT: &'static str = "199
-200
-208
-210
-200
-207
-240
-269
-260
-263";
+pub fn solver() -> impl Solver {
+    Solution {
+        day: 1,
+        title: "Sonar Sweep",
+        examples: &[Example {
+            input: EXAMPLE,
+            part1: Some("7"),
+            part2: Some("5"),
+        }],
+        parse: ints,
+        valid: |_| true,
+        part1: |x| part1(x),
+        part2: |x| part2(x),
+        variants: vec![Variant {
+            name: oracle::VARIANT,
+            part1: Some(|x| oracle::day1::part1(x)),
+            part2: Some(|x| oracle::day1::part2(x)),
+        }],
+    }
+}
 
-    use super::{ints, part1, part2};
-    use std::num::ParseIntError;
+#[cfg(test)]
+pub mod tests {
+    use super::{ints, part1, part2, EXAMPLE};
+    use crate::error::AocError;
 
     #[test]
-    fn solve_day_1() -> Result<(), ParseIntError> {
-        let input = ints(INPUT)?;
+    fn solve_day_1() -> Result<(), AocError> {
+        let input = ints(EXAMPLE)?;
         assert_eq!(part1(&input), 7);
         assert_eq!(part2(&input), 5);
 
@@ -1,38 +1,107 @@
-#[aoc_generator(day10)]
-fn parse(input: &str) -> Vec<String> {
-    input.lines().map(|l| l.to_string()).collect()
+use crate::error::{lint_lines, AocError, ParseErrorKind};
+use crate::input::normalize;
+#[cfg(feature = "std")]
+use crate::oracle;
+#[cfg(feature = "std")]
+use crate::solver::{Example, Solution, Solver, Variant};
+#[cfg(feature = "std")]
+use crate::stream::for_each_line;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::io::BufRead;
+
+/// Checks that a line is only made of chunk delimiters
+fn check_line(l: &str) -> Result<(), AocError> {
+    match l.chars().position(|c| !"()[]{}<>".contains(c)) {
+        Some(column) => {
+            let found = l.chars().nth(column).unwrap(); // safe because position found it
+            let kind = ParseErrorKind::UnexpectedChar(found);
+            Err(AocError::parse(10, 1, column + 1, kind))
+        }
+        None => Ok(()),
+    }
 }
 
-#[aoc(day10, part1)]
-fn part1(input: &[String]) -> u32 {
+/// Parses one line of chunks per line, only checking the characters
+#[cfg_attr(feature = "std", aoc_generator(day10))]
+pub fn parse(input: &str) -> Result<Vec<String>, AocError> {
+    let input = &normalize(input);
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, l)| {
+            check_line(l).map_err(|e| e.at_line(idx + 1))?;
+            Ok(l.to_string())
+        })
+        .collect()
+}
+
+/// Every line with other characters than chunk delimiters
+pub fn lint(input: &str) -> Vec<AocError> {
+    lint_lines(input, check_line)
+}
+
+#[cfg_attr(feature = "std", aoc(day10, part1))]
+pub fn part1(input: &[String]) -> u32 {
     input
         .iter()
-        .filter_map(|l| match do_parse_line(&l) {
-            ParseResult::Corrupted(c) => Some(illegal_score(c)),
-            ParseResult::Incomplete(_) => None,
-            ParseResult::Complete => None,
+        .filter_map(|l| match check_chunks(l) {
+            ChunkStatus::Corrupted(c) => Some(illegal_score(c)),
+            ChunkStatus::Incomplete(_) => None,
+            ChunkStatus::Complete => None,
         })
         .sum()
 }
 
-#[aoc(day10, part2)]
-fn part2(input: &[String]) -> u64 {
+#[cfg_attr(feature = "std", aoc(day10, part2))]
+pub fn part2(input: &[String]) -> Result<u64, AocError> {
     let mut scores: Vec<u64> = input
         .iter()
-        .filter_map(|l| match do_parse_line(&l) {
-            ParseResult::Corrupted(_) => None,
-            ParseResult::Incomplete(stack) => {
-                let score = stack
-                    .into_iter()
-                    .rev()
-                    .fold(0, |total, c| total * 5 + completion_score(c));
-                Some(score)
-            }
-            ParseResult::Complete => None,
+        .filter_map(|l| match check_chunks(l) {
+            ChunkStatus::Corrupted(_) => None,
+            ChunkStatus::Incomplete(stack) => Some(autocomplete_score(stack)),
+            ChunkStatus::Complete => None,
         })
-        .collect();
-    scores.sort();
-    scores[scores.len() / 2]
+        .collect::<Result<_, _>>()?;
+    middle_score(&mut scores)
+}
+
+/// Both parts in a single pass, only keeping the score of each incomplete line as part 2
+/// needs the middle one
+#[cfg(feature = "std")]
+pub fn stream(input: impl BufRead) -> Result<(u64, Result<u64, AocError>), AocError> {
+    let mut syntax_score = 0u64;
+    let mut scores = Vec::new();
+    for_each_line(10, input, |line| {
+        check_line(line)?;
+        match check_chunks(line) {
+            ChunkStatus::Corrupted(c) => syntax_score += u64::from(illegal_score(c)),
+            ChunkStatus::Incomplete(stack) => scores.push(autocomplete_score(stack)?),
+            ChunkStatus::Complete => (),
+        }
+        Ok(())
+    })?;
+    Ok((syntax_score, middle_score(&mut scores)))
+}
+
+/// Score of the closing characters completing an incomplete line, given its unclosed chunks
+/// from the outermost to the innermost. It outgrows a `u64` past 27 unclosed chunks.
+pub fn autocomplete_score(stack: Vec<char>) -> Result<u64, AocError> {
+    stack.into_iter().rev().try_fold(0u64, |total, c| {
+        total
+            .checked_mul(5)
+            .and_then(|total| total.checked_add(completion_score(c)))
+            .ok_or(AocError::Overflow(10))
+    })
+}
+
+fn middle_score(scores: &mut [u64]) -> Result<u64, AocError> {
+    if scores.is_empty() {
+        return Err(AocError::no_solution(10, "no line is incomplete"));
+    }
+    let middle = scores.len() / 2;
+    Ok(*scores.select_nth_unstable(middle).1)
 }
 
 fn completion_score(c: char) -> u64 {
@@ -45,7 +114,8 @@ fn completion_score(c: char) -> u64 {
     }
 }
 
-fn illegal_score(c: char) -> u32 {
+/// Score of the first illegal closing character of a corrupted line
+pub fn illegal_score(c: char) -> u32 {
     match c {
         ')' => 3,
         ']' => 57,
@@ -55,101 +125,142 @@ fn illegal_score(c: char) -> u32 {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-enum ParseResult {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChunkStatus {
     Complete,
+    /// The opening characters of the unclosed chunks, from the outermost to the innermost
     Incomplete(Vec<char>),
+    /// The first closing character that doesn't match its chunk
     Corrupted(char),
 }
 
-fn parse_line(input: &str, mut stack: Vec<char>) -> ParseResult {
-    if input.len() == 0 {
-        return match stack.len() {
-            0 => ParseResult::Complete,
-            _ => ParseResult::Incomplete(stack),
+/// Matches the chunks of a line
+pub fn check_chunks(input: &str) -> ChunkStatus {
+    let mut stack = Vec::new();
+    for c in input.chars() {
+        let opening = match c {
+            '(' | '[' | '{' | '<' => {
+                stack.push(c);
+                continue;
+            }
+            ')' => '(',
+            ']' => '[',
+            '}' => '{',
+            _ => '<',
         };
-    }
-    let c = input.chars().next().unwrap(); // safe because input.len() > 0
-    match c {
-        '(' | '[' | '{' | '<' => {
-            stack.push(c);
-            parse_line(&input[1..], stack)
-        }
-        ')' if stack.iter().rev().peekable().peek() == Some(&&'(') => {
-            stack.pop();
-            parse_line(&input[1..], stack)
+        if stack.pop() != Some(opening) {
+            return ChunkStatus::Corrupted(c);
         }
-        ']' if stack.iter().rev().peekable().peek() == Some(&&'[') => {
-            stack.pop();
-            parse_line(&input[1..], stack)
-        }
-        '>' if stack.iter().rev().peekable().peek() == Some(&&'<') => {
-            stack.pop();
-            parse_line(&input[1..], stack)
-        }
-        '}' if stack.iter().rev().peekable().peek() == Some(&&'{') => {
-            stack.pop();
-            parse_line(&input[1..], stack)
-        }
-        _ => ParseResult::Corrupted(c),
+    }
+    match stack.len() {
+        0 => ChunkStatus::Complete,
+        _ => ChunkStatus::Incomplete(stack),
     }
 }
 
-fn do_parse_line(input: &str) -> ParseResult {
-    let stack = Vec::new();
-    parse_line(input, stack)
+#[cfg(any(test, feature = "std"))]
+const EXAMPLE: &str = include_str!("../examples/day10/example.txt");
+
+#[cfg(feature = "std")]
+pub fn solver() -> impl Solver {
+    Solution {
+        day: 10,
+        title: "Syntax Scoring",
+        examples: &[Example {
+            input: EXAMPLE,
+            part1: Some("26397"),
+            part2: Some("288957"),
+        }],
+        parse,
+        valid: |lines| lines.iter().all(|l| check_line(l).is_ok()),
+        part1: |x| part1(x),
+        part2: |x| part2(x),
+        variants: vec![Variant {
+            name: oracle::VARIANT,
+            part1: Some(|x| oracle::day10::part1(x)),
+            part2: Some(|x| oracle::day10::part2(x)),
+        }],
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
 
-    const INPUT: &'static str = "[({(<(())[]>[[{[]{<()<>>
-[(()[<>])]({[<{<<[]>>(
-{([(<{}[<>[]}>{[]{[(<()>
-(((({<>}<{<{<>}{[]{[]{}
-[[<[([]))<([[{}[[()]]]
-[{[{({}]{}}([{[{{{}}([]
-{<[[]]>}<{[{[{[]{()[[[]
-[<(<(<(<{}))><([]([]()
-<{([([[(<>()){}]>(<<{{
-<{([{{}}[<[[[<>{}]]]>[]]";
-
     #[test]
     fn valid_chunks() {
-        assert_eq!(do_parse_line("()"), ParseResult::Complete);
-        assert_eq!(do_parse_line("[]"), ParseResult::Complete);
-        assert_eq!(do_parse_line("([])"), ParseResult::Complete);
-        assert_eq!(do_parse_line("{()()()}"), ParseResult::Complete);
-        assert_eq!(do_parse_line("<([{}])>"), ParseResult::Complete);
-        assert_eq!(do_parse_line("[<>({}){}[([])<>]]"), ParseResult::Complete);
-        assert_eq!(do_parse_line("(((((((((())))))))))"), ParseResult::Complete);
+        assert_eq!(check_chunks("()"), ChunkStatus::Complete);
+        assert_eq!(check_chunks("[]"), ChunkStatus::Complete);
+        assert_eq!(check_chunks("([])"), ChunkStatus::Complete);
+        assert_eq!(check_chunks("{()()()}"), ChunkStatus::Complete);
+        assert_eq!(check_chunks("<([{}])>"), ChunkStatus::Complete);
+        assert_eq!(check_chunks("[<>({}){}[([])<>]]"), ChunkStatus::Complete);
+        assert_eq!(check_chunks("(((((((((())))))))))"), ChunkStatus::Complete);
     }
 
     #[test]
     fn incomplete_chunks() {
         assert_eq!(
-            do_parse_line("[(()[<>])]({[<{<<[]>>("),
-            ParseResult::Incomplete(vec!['(', '{', '[', '<', '{', '(',])
+            check_chunks("[(()[<>])]({[<{<<[]>>("),
+            ChunkStatus::Incomplete(vec!['(', '{', '[', '<', '{', '(',])
         );
         assert_eq!(
-            do_parse_line("{<(<{[[([[(<[(<>[])]<{()}<()[]>>><{<{}[]><[]>}(<<>{}><()()>)>)]]{((({{<><>}{{}{}}}{<[]()><{}<>>})<[{("),
-            ParseResult::Incomplete(vec![ '{', '<', '(', '<', '{', '[', '[', '(', '{', '(', '(', '<', '[', '{', '(' ])
+            check_chunks("{<(<{[[([[(<[(<>[])]<{()}<()[]>>><{<{}[]><[]>}(<<>{}><()()>)>)]]{((({{<><>}{{}{}}}{<[]()><{}<>>})<[{("),
+            ChunkStatus::Incomplete(vec![ '{', '<', '(', '<', '{', '[', '[', '(', '{', '(', '(', '<', '[', '{', '(' ])
         );
     }
 
     #[test]
     fn corrupted_chunks() {
         assert_eq!(
-            do_parse_line("{([(<{}[<>[]}>{[]{[(<()>"),
-            ParseResult::Corrupted('}'),
+            check_chunks("{([(<{}[<>[]}>{[]{[(<()>"),
+            ChunkStatus::Corrupted('}'),
+        );
+        assert_eq!(check_chunks("(]"), ChunkStatus::Corrupted(']'));
+        assert_eq!(check_chunks(">"), ChunkStatus::Corrupted('>'));
+    }
+
+    #[test]
+    fn should_handle_long_lines() {
+        let line = "(".repeat(500_000) + &")".repeat(500_000);
+        assert_eq!(check_chunks(&line), ChunkStatus::Complete);
+        let line = "[".repeat(1_000_000);
+        assert!(matches!(check_chunks(&line), ChunkStatus::Incomplete(s) if s.len() == 1_000_000));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn should_stream_large_syntax_scores() -> Result<(), AocError> {
+        let input = "<>>\n".repeat(200_000) + "(";
+        assert_eq!(stream(input.as_bytes())?, (200_000 * 25137, Ok(1)));
+        Ok(())
+    }
+
+    #[test]
+    fn should_report_overflows() -> Result<(), AocError> {
+        assert_eq!(autocomplete_score(vec!['<'; 27])?, 5u64.pow(27) - 1);
+        assert_eq!(
+            autocomplete_score(vec!['('; 28])?,
+            9_313_225_746_154_785_156
+        );
+        assert_eq!(
+            autocomplete_score(vec!['<'; 28]),
+            Err(AocError::Overflow(10))
+        );
+        assert_eq!(
+            autocomplete_score(vec!['('; 29]),
+            Err(AocError::Overflow(10))
         );
+        let data = parse(&"(".repeat(40))?;
+        assert_eq!(part2(&data), Err(AocError::Overflow(10)));
+        Ok(())
     }
 
     #[test]
-    fn should_work() {
-        let data = parse(INPUT);
+    fn should_work() -> Result<(), AocError> {
+        let data = parse(EXAMPLE)?;
         assert_eq!(part1(&data), 26397);
-        assert_eq!(part2(&data), 288957);
+        assert_eq!(part2(&data)?, 288957);
+        Ok(())
     }
 }
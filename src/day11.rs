@@ -1,27 +1,32 @@
+use crate::error::AocError;
+use crate::grid::{Grid, Position};
+use crate::input::normalize;
+use crate::oracle;
+use crate::solver::{Example, Solution, Solver, Variant};
+use crate::trace::event;
+use crate::viz::{Frame, Visualize};
 use std::collections::HashSet;
 
-struct OctopusMap {
-    octopuses: Vec<Vec<u8>>,
-}
-
-fn all_positions() -> impl Iterator<Item = (i32, i32)> {
-    (0..10).flat_map(|x| (0..10).map(move |y| (x, y)))
-}
-
-fn neighbors(pos: (i32, i32)) -> impl Iterator<Item = (i32, i32)> {
-    (pos.0 - 1..=pos.0 + 1)
-        .flat_map(move |x| (pos.1 - 1..=pos.1 + 1).map(move |y| (x, y)))
-        .filter(move |(x, y)| {
-            !(*x == pos.0 && *y == pos.1) && (*x >= 0 && *x <= 9) && (*y >= 0 && *y <= 9)
-        })
+/// Energy levels of the octopuses, from 0 to 9 once parsed, higher ones flashing on the next step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OctopusMap {
+    pub octopuses: Grid<u8>,
+    /// Steps taken so far
+    pub steps: usize,
 }
 
 impl OctopusMap {
+    pub fn new(octopuses: Grid<u8>) -> Self {
+        OctopusMap {
+            octopuses,
+            steps: 0,
+        }
+    }
+
+    /// Moves one step forward, returning the number of octopuses that flashed
     pub fn step(&mut self) -> usize {
-        for l in &mut self.octopuses {
-            for o in l {
-                *o += 1;
-            }
+        for o in self.octopuses.iter_mut() {
+            *o = o.saturating_add(1);
         }
         let mut flashes = HashSet::default();
         loop {
@@ -31,73 +36,154 @@ impl OctopusMap {
                 break;
             }
         }
-        for l in &mut self.octopuses {
-            for o in l {
-                if *o >= 10 {
-                    *o = 0;
-                }
+        for o in self.octopuses.iter_mut() {
+            if *o >= 10 {
+                *o = 0;
             }
         }
+        self.steps += 1;
+        event!(step = self.steps, flashes = flashes.len());
         flashes.len()
     }
 
-    pub fn flashes(&mut self, flashes: &mut HashSet<(i32, i32)>) {
-        for pos in all_positions() {
+    fn flashes(&mut self, flashes: &mut HashSet<Position>) {
+        for pos in self.octopuses.positions() {
             if flashes.contains(&pos) {
                 continue;
             }
-            let octopus = self.octopuses[pos.1 as usize][pos.0 as usize];
-            if octopus >= 10 {
+            if self.octopuses[pos] >= 10 {
                 flashes.insert(pos);
-                neighbors(pos).for_each(|p| self.octopuses[p.1 as usize][p.0 as usize] += 1);
+                let neighbors: Vec<Position> = self.octopuses.all_neighbors(pos).collect();
+                neighbors
+                    .into_iter()
+                    .for_each(|p| self.octopuses[p] = self.octopuses[p].saturating_add(1));
+            }
+        }
+    }
+
+    /// Moves forward until every octopus flashes at once, returning the number of steps taken.
+    /// `None` if they never do, which is noticed once the map goes back to an earlier state.
+    pub fn synchronize(&mut self) -> Option<usize> {
+        // Checkpoints are taken at powers of two to find loops of any length
+        let mut checkpoint = self.octopuses.clone();
+        let mut next_checkpoint = 1;
+        for step in 1.. {
+            if self.step() == self.octopuses.len() {
+                return Some(step);
+            }
+            if self.octopuses == checkpoint {
+                return None;
+            }
+            if step == next_checkpoint {
+                checkpoint = self.octopuses.clone();
+                next_checkpoint *= 2;
+            }
+        }
+
+        unreachable!()
+    }
+}
+
+/// Energy levels after each step, until all octopuses flash at once.
+/// Octopuses that just flashed are white, the others get brighter as their energy rises.
+pub struct Flashes<'a>(pub &'a Grid<u8>);
+
+/// Maps that never synchronize are cut there
+const MAX_FRAMES: usize = 1000;
+
+impl Visualize for Flashes<'_> {
+    fn frames(&self) -> Vec<Frame> {
+        let mut map = OctopusMap::new(self.0.clone());
+        let frame = |octopuses: &Grid<u8>| {
+            Frame::Gray(octopuses.map(|&energy| if energy == 0 { 255 } else { energy * 20 }))
+        };
+        let mut frames = vec![frame(&map.octopuses)];
+        while frames.len() < MAX_FRAMES {
+            let flashes = map.step();
+            frames.push(frame(&map.octopuses));
+            if flashes == self.0.len() {
+                break;
             }
         }
+        frames
     }
 }
 
+/// Parses one row of energy levels per line
 #[aoc_generator(day11)]
-fn parse(input: &str) -> Vec<Vec<u8>> {
-    input
-        .lines()
-        .map(|l| l.chars().map(|c| c.to_digit(10).unwrap() as u8).collect())
-        .collect()
+pub fn parse(input: &str) -> Result<Grid<u8>, AocError> {
+    let input = &normalize(input);
+    Grid::parse_digits(input).map_err(|e| AocError::from_grid(11, e))
+}
+
+/// Every row with other cells than digits or a different width than the first one
+pub fn lint(input: &str) -> Vec<AocError> {
+    let errors = Grid::lint_digits(input).into_iter();
+    errors.map(|e| AocError::from_grid(11, e)).collect()
 }
 
 #[aoc(day11, part1)]
-fn part1(input: &Vec<Vec<u8>>) -> usize {
-    let mut map = OctopusMap {
-        octopuses: input.clone(),
-    };
+pub fn part1(input: &Grid<u8>) -> usize {
+    let mut map = OctopusMap::new(input.clone());
     (0..100).map(|_| map.step()).sum()
 }
 
 #[aoc(day11, part2)]
-fn part2(input: &Vec<Vec<u8>>) -> usize {
-    let mut map = OctopusMap {
-        octopuses: input.clone(),
-    };
-    (1..).find(|_| map.step() == 100).unwrap()
+pub fn part2(input: &Grid<u8>) -> Result<usize, AocError> {
+    OctopusMap::new(input.clone())
+        .synchronize()
+        .ok_or_else(|| AocError::no_solution(11, "octopuses never flash all at once"))
+}
+
+const EXAMPLE: &str = include_str!("../examples/day11/example.txt");
+
+pub fn solver() -> impl Solver {
+    Solution {
+        day: 11,
+        title: "Dumbo Octopus",
+        examples: &[Example {
+            input: EXAMPLE,
+            part1: Some("1656"),
+            part2: Some("195"),
+        }],
+        parse,
+        valid: |energy| !energy.is_empty() && energy.iter().all(|&e| e <= 9),
+        part1,
+        part2,
+        variants: vec![Variant {
+            name: oracle::VARIANT,
+            part1: Some(oracle::day11::part1),
+            part2: Some(oracle::day11::part2),
+        }],
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
 
-    const INPUT: &'static str = "5483143223
-2745854711
-5264556173
-6141336146
-6357385478
-4167524645
-2176841721
-6882881134
-4846848554
-5283751526";
-
     #[test]
-    fn should_work() {
-        let data = parse(INPUT);
+    fn should_work() -> Result<(), AocError> {
+        let data = parse(EXAMPLE)?;
         assert_eq!(part1(&data), 1656);
-        assert_eq!(part2(&data), 195);
+        assert_eq!(part2(&data)?, 195);
+        Ok(())
+    }
+
+    #[test]
+    fn should_detect_endless_loops() -> Result<(), AocError> {
+        let data = parse("590\n518\n530")?;
+        assert!(matches!(
+            part2(&data),
+            Err(AocError::NoSolution { day: 11, .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn should_flash_any_energy_level() {
+        let mut map = OctopusMap::new(Grid::from_vec(2, vec![255, 250, 9, 0]));
+        assert_eq!(map.step(), 3);
+        assert_eq!(map.octopuses, Grid::from_vec(2, vec![0, 0, 0, 4]));
     }
 }
@@ -1,48 +1,155 @@
+use crate::arith;
+use crate::error::{column_of, lint_lines, split_pair, AocError, ParseErrorKind};
+use crate::input::normalize;
+use crate::oracle;
+use crate::solver::{Example, Solution, Solver, Variant};
 use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
+/// Most small caves a map can have, `start` and `end` included, as paths are counted for each set
+/// of visited small caves
+const MAX_SMALL_CAVES: usize = 16;
+
+/// A cave, named in uppercase for big caves and lowercase for small ones
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
-enum Cave {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Cave {
     Big(String),
     Small(String),
 }
 
+impl Cave {
+    pub fn name(&self) -> &str {
+        match self {
+            Cave::Big(name) | Cave::Small(name) => name,
+        }
+    }
+}
+
 impl FromStr for Cave {
-    type Err = std::convert::Infallible;
+    type Err = AocError;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        if input.chars().next().unwrap().is_lowercase() {
-            Ok(Cave::Small(input.to_string()))
-        } else {
-            Ok(Cave::Big(input.to_string()))
+        if let Some(idx) = input.find(|c: char| !c.is_ascii_alphabetic()) {
+            let found = input[idx..].chars().next().unwrap(); // safe because idx < input.len()
+            return Err(AocError::parse(
+                12,
+                1,
+                idx + 1,
+                ParseErrorKind::UnexpectedChar(found),
+            ));
+        }
+        match input.chars().next() {
+            Some(c) if c.is_lowercase() => Ok(Cave::Small(input.to_string())),
+            Some(_) => Ok(Cave::Big(input.to_string())),
+            None => Err(AocError::parse(
+                12,
+                1,
+                1,
+                ParseErrorKind::Expected("a cave name"),
+            )),
         }
     }
 }
 
+/// Parses a `a-b` link between two caves
+fn parse_link(line: &str) -> Result<(Cave, Cave), AocError> {
+    let (a, b) = split_pair(12, line, "-", "`-`")?;
+    let parse_cave = |cave: &str| {
+        cave.parse::<Cave>()
+            .map_err(|e| e.at_column(column_of(line, cave)))
+    };
+    match (parse_cave(a)?, parse_cave(b)?) {
+        // Paths could go back and forth between them forever
+        (Cave::Big(_), Cave::Big(_)) => Err(AocError::parse(
+            12,
+            1,
+            1,
+            ParseErrorKind::Expected("at least one small cave"),
+        )),
+        link => Ok(link),
+    }
+}
+
+/// Caves linked to each cave. Links are both ways, except for `start` which is never gone back to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CaveMap {
     links: HashMap<Cave, HashSet<Cave>>,
 }
 
 impl CaveMap {
-    fn non_visited_neighbors(
-        &self,
-        visited: &HashSet<Cave>,
-        visit_flag: bool,
-        cave: &Cave,
-    ) -> HashSet<Cave> {
-        if visit_flag {
-            return self.links[cave].clone();
+    pub fn neighbors(&self, cave: &Cave) -> impl Iterator<Item = &Cave> {
+        self.links.get(cave).into_iter().flatten()
+    }
+
+    /// Number of paths from `start` to `end` visiting small caves at most once, except for a
+    /// single one visited twice if `visit_twice`
+    pub fn count_paths(&self, visit_twice: bool) -> Result<usize, AocError> {
+        let start = Cave::Small("start".to_string());
+        // Small caves are numbered to keep the visited ones in a bit set
+        let small: HashMap<&Cave, u32> = self
+            .links
+            .iter()
+            .flat_map(|(cave, links)| links.iter().chain([cave]))
+            .filter(|c| matches!(c, Cave::Small(_)))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .zip(0..)
+            .collect();
+        let visited = small.get(&start).map_or(0, |&idx| 1u64 << idx);
+        let mut counted = HashMap::new();
+        self.count_paths_from(&start, visited, visit_twice, &small, &mut counted)
+    }
+
+    /// Number of paths from `cave` to `end` once the small caves in `visited` were visited, which
+    /// only depends on these, so that it is counted once for them in `counted`
+    fn count_paths_from<'a>(
+        &'a self,
+        cave: &'a Cave,
+        visited: u64,
+        can_visit_twice: bool,
+        small: &HashMap<&Cave, u32>,
+        counted: &mut HashMap<(&'a Cave, u64, bool), usize>,
+    ) -> Result<usize, AocError> {
+        if cave.name() == "end" {
+            return Ok(1);
+        }
+        if let Some(&count) = counted.get(&(cave, visited, can_visit_twice)) {
+            return Ok(count);
         }
-        &self.links[cave] - visited
+        let mut count = 0;
+        for next in self.neighbors(cave) {
+            let paths = match small.get(next) {
+                None => self.count_paths_from(next, visited, can_visit_twice, small, counted)?,
+                Some(&idx) if visited & 1 << idx == 0 => {
+                    let visited = visited | 1 << idx;
+                    self.count_paths_from(next, visited, can_visit_twice, small, counted)?
+                }
+                Some(_) if can_visit_twice => {
+                    self.count_paths_from(next, visited, false, small, counted)?
+                }
+                Some(_) => 0,
+            };
+            count = arith::add(12, count, paths)?;
+        }
+        counted.insert((cave, visited, can_visit_twice), count);
+        Ok(count)
     }
 }
 
+/// Parses one `a-b` link per line
 #[aoc_generator(day12)]
-fn parse(input: &str) -> CaveMap {
+pub fn parse(input: &str) -> Result<CaveMap, AocError> {
+    let input = &normalize(input);
     let mut links = HashMap::<Cave, HashSet<Cave>>::default();
     let start = Cave::Small("start".to_string());
-    input.lines().for_each(|l| {
-        let caves: Vec<Cave> = l.split("-").map(|c| c.parse().unwrap()).collect();
+    let mut small = HashSet::new();
+    for (idx, l) in input.lines().enumerate() {
+        let link = parse_link(l)
+            .and_then(|link| add_small_caves(&link, &mut small).map(|_| link))
+            .map_err(|e| e.at_line(idx + 1))?;
+        let caves = [link.0, link.1];
 
         if caves[1] != start {
             let cave_0 = links.entry(caves[0].clone()).or_default();
@@ -53,136 +160,131 @@ fn parse(input: &str) -> CaveMap {
             let cave_1 = links.entry(caves[1].clone()).or_default();
             cave_1.insert(caves[0].clone());
         }
-    });
-    CaveMap { links }
-}
-
-#[derive(Debug)]
-pub struct CavePath {
-    head: Cave,
-    closed: HashSet<Cave>,
-    can_visit_twice: bool,
+    }
+    Ok(CaveMap { links })
 }
 
-#[aoc(day12, part1)]
-fn part1(input: &CaveMap) -> usize {
-    let mut open: Vec<CavePath> = Vec::new();
-    let mut total_path = 0;
-
-    open.push(CavePath {
-        head: Cave::Small("start".to_string()),
-        closed: HashSet::default(),
-        can_visit_twice: true,
-    });
-    loop {
-        if open.len() == 0 {
-            break;
-        }
-        let CavePath {
-            head,
-            mut closed,
-            can_visit_twice,
-        } = open.pop().unwrap();
-        match head {
-            Cave::Small(x) if x == "end" => {
-                total_path += 1;
-                continue;
-            }
-            Cave::Small(_) => {
-                closed.insert(head.clone());
-            }
-            Cave::Big(_) => (),
+/// Adds the small caves of `link` to `small`, failing once there are more than `MAX_SMALL_CAVES`
+fn add_small_caves(link: &(Cave, Cave), small: &mut HashSet<Cave>) -> Result<(), AocError> {
+    let (a, b) = link;
+    for (cave, column) in [(a, 1), (b, a.name().len() + 2)] {
+        if matches!(cave, Cave::Small(_))
+            && small.insert(cave.clone())
+            && small.len() > MAX_SMALL_CAVES
+        {
+            let kind = ParseErrorKind::Expected("at most 16 small caves");
+            return Err(AocError::parse(12, 1, column, kind));
         }
-        input
-            .non_visited_neighbors(&closed, false, &head)
-            .into_iter()
-            .for_each(|c| {
-                open.push(CavePath {
-                    head: c,
-                    closed: closed.clone(),
-                    can_visit_twice,
-                })
-            });
     }
+    Ok(())
+}
 
-    total_path
+/// Whether `caves` could come from `parse`: no two big caves linked to each other, and at most
+/// `MAX_SMALL_CAVES` small ones
+fn valid(caves: &CaveMap) -> bool {
+    let is_small = |cave: &Cave| matches!(cave, Cave::Small(_));
+    let small: HashSet<&Cave> = caves
+        .links
+        .iter()
+        .flat_map(|(cave, links)| links.iter().chain([cave]))
+        .filter(|c| is_small(c))
+        .collect();
+    let big_links = caves
+        .links
+        .iter()
+        .any(|(cave, links)| !is_small(cave) && !links.iter().all(is_small));
+    small.len() <= MAX_SMALL_CAVES && !big_links
+}
+
+/// Every line that isn't a link between two caves, one of them small, or that has one small cave
+/// too many
+pub fn lint(input: &str) -> Vec<AocError> {
+    let mut small = HashSet::new();
+    lint_lines(input, |l| add_small_caves(&parse_link(l)?, &mut small))
+}
+
+#[aoc(day12, part1)]
+pub fn part1(input: &CaveMap) -> Result<usize, AocError> {
+    input.count_paths(false)
 }
 
 #[aoc(day12, part2)]
-fn part2(input: &CaveMap) -> usize {
-    let mut open: Vec<CavePath> = Vec::new();
-    let mut total_path = 0;
-    let closed = HashSet::default();
-    open.push(CavePath {
-        head: Cave::Small("start".to_string()),
-        closed,
-        can_visit_twice: true,
-    });
-    loop {
-        if open.len() == 0 {
-            break;
-        }
-        let CavePath {
-            head,
-            mut closed,
-            can_visit_twice,
-        } = open.pop().unwrap();
-        match head {
-            Cave::Small(x) if x == "end" => {
-                total_path += 1;
-                continue;
-            }
-            Cave::Small(_) => {
-                closed.insert(head.clone());
-            }
-            Cave::Big(_) => (),
-        }
-        input
-            .non_visited_neighbors(&closed, can_visit_twice, &head)
-            .into_iter()
-            .for_each(|c| {
-                let can_visit_twice = can_visit_twice && !closed.contains(&c);
-                open.push(CavePath {
-                    head: c,
-                    closed: closed.clone(),
-                    can_visit_twice,
-                })
-            });
-    }
+pub fn part2(input: &CaveMap) -> Result<usize, AocError> {
+    input.count_paths(true)
+}
+
+const EXAMPLE: &str = include_str!("../examples/day12/example1.txt");
 
-    total_path
+const EXAMPLE_B: &str = include_str!("../examples/day12/example2.txt");
+
+pub fn solver() -> impl Solver {
+    Solution {
+        day: 12,
+        title: "Passage Pathing",
+        examples: &[
+            Example {
+                input: EXAMPLE,
+                part1: Some("10"),
+                part2: Some("36"),
+            },
+            Example {
+                input: EXAMPLE_B,
+                part1: Some("19"),
+                part2: Some("103"),
+            },
+        ],
+        parse,
+        valid,
+        part1,
+        part2,
+        variants: vec![Variant {
+            name: oracle::VARIANT,
+            part1: Some(|x| Ok(oracle::day12::part1(x))),
+            part2: Some(|x| Ok(oracle::day12::part2(x))),
+        }],
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
 
-    const INPUT: &'static str = "start-A
-start-b
-A-c
-A-b
-b-d
-A-end
-b-end";
-
-    const INPUT_B: &'static str = "dc-end
-HN-start
-start-kj
-dc-start
-dc-HN
-LN-dc
-HN-end
-kj-sa
-kj-HN
-kj-dc";
+    #[test]
+    fn should_work() -> Result<(), AocError> {
+        let data = parse(EXAMPLE)?;
+        assert_eq!(part1(&data)?, 10);
+        assert_eq!(part2(&data)?, 36);
+
+        let data = parse(EXAMPLE_B)?;
+        assert_eq!(part2(&data)?, 103);
+        Ok(())
+    }
+
+    #[test]
+    fn should_reject_linked_big_caves() {
+        let err = parse("start-A\nA-B\nB-end").err();
+        assert!(matches!(err, Some(AocError::Parse { line: 2, .. })));
+    }
+
+    #[test]
+    fn should_handle_dead_ends() -> Result<(), AocError> {
+        assert_eq!(part1(&parse("a-end")?)?, 0);
+        assert_eq!(part2(&parse("")?)?, 0);
+        Ok(())
+    }
 
     #[test]
-    fn should_work() {
-        let data = parse(INPUT);
-        assert_eq!(part1(&data), 10);
-        assert_eq!(part2(&data), 36);
+    fn should_count_countless_paths() -> Result<(), AocError> {
+        // 14 small caves around a big one, the most a map can have with `start` and `end`
+        let caves = "abcdefghijklmn".chars().map(|c| format!("A-{}", c));
+        let input = format!("start-A\nA-end\n{}", caves.collect::<Vec<_>>().join("\n"));
+        let data = parse(&input)?;
+        assert_eq!(part1(&data)?, 236975164805);
+        assert_eq!(part2(&data)?, 21920202744469);
 
-        let data = parse(INPUT_B);
-        assert_eq!(part2(&data), 103);
+        let err = parse(&(input + "\no-A")).err();
+        let kind = ParseErrorKind::Expected("at most 16 small caves");
+        assert_eq!(err, Some(AocError::parse(12, 17, 1, kind)));
+        Ok(())
     }
 }
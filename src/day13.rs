@@ -1,97 +1,170 @@
+use crate::error::{first_error, parse_bounded, split_pair, AocError, ParseErrorKind};
+use crate::geom::{Bounds, Point2};
+use crate::grid::Grid;
+use crate::input::normalize;
+use crate::oracle;
+use crate::solver::{Example, Solution, Solver, Variant};
+use crate::viz::{Frame, Visualize};
 use std::{collections::HashSet, str::FromStr};
 
-#[derive(Clone)]
-struct Point {
-    x: i32,
-    y: i32,
-}
+/// Largest coordinate of a dot or of a fold line, the drawings growing with its square
+const MAX_COORDINATE: i32 = 4095;
 
-enum FoldInstruction {
+/// A fold along a vertical (`x=`) or horizontal (`y=`) line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FoldInstruction {
     X(i32),
     Y(i32),
 }
 
 impl FromStr for FoldInstruction {
-    type Err = std::convert::Infallible;
+    type Err = AocError;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let input = input.replace("fold along ", "");
-        let input: Vec<&str> = input.split("=").collect();
-        let value = input[1].parse().unwrap();
-        match input[0] {
+        let instruction = input
+            .strip_prefix("fold along ")
+            .ok_or_else(|| AocError::parse(13, 1, 1, ParseErrorKind::Expected("`fold along `")))?;
+        let (axis, value) = split_pair(13, instruction, "=", "`=`")
+            .map_err(|e| e.at_column(input.len() - instruction.len() + 1))?;
+        let value = parse_bounded(
+            13,
+            input,
+            value,
+            0..=MAX_COORDINATE,
+            "a fold line from 0 to 4095",
+        )?;
+        match axis {
             "x" => Ok(FoldInstruction::X(value)),
             "y" => Ok(FoldInstruction::Y(value)),
-            _ => panic!("Unknown axis"),
+            x => Err(AocError::parse(
+                13,
+                1,
+                input.len() - instruction.len() + 1,
+                ParseErrorKind::UnknownToken(x.to_string()),
+            )),
         }
     }
 }
 
-impl FromStr for Point {
-    type Err = std::convert::Infallible;
+/// Parses a `x,y` dot
+fn parse_point(input: &str) -> Result<Point2<i32>, AocError> {
+    let (x, y) = split_pair(13, input, ",", "`,`")?;
+    let coordinate = |field| {
+        parse_bounded(
+            13,
+            input,
+            field,
+            0..=MAX_COORDINATE,
+            "a coordinate from 0 to 4095",
+        )
+    };
+    Ok(Point2::new(coordinate(x)?, coordinate(y)?))
+}
 
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let input: Vec<&str> = input.split(",").collect();
-        Ok(Point {
-            x: input[0].parse().unwrap(),
-            y: input[1].parse().unwrap(),
-        })
-    }
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Paper {
+    pub points: Vec<Point2<i32>>,
+    pub instructions: Vec<FoldInstruction>,
 }
 
-struct Paper {
-    points: Vec<Point>,
-    instructions: Vec<FoldInstruction>,
+/// Same as `parse`, carrying on after faulty lines so that every problem is found
+fn read(input: &str) -> (Paper, Vec<AocError>) {
+    let mut points = Vec::new();
+    let mut instructions = Vec::new();
+    let mut errors = Vec::new();
+    // Points come first, then a blank line, then fold instructions
+    let mut in_points = true;
+    let mut fold_lines = 0;
+    for (idx, l) in input.lines().enumerate() {
+        let parsed = if in_points && l.is_empty() {
+            in_points = false;
+            Ok(())
+        } else if in_points {
+            parse_point(l).map(|p| points.push(p))
+        } else {
+            fold_lines += 1;
+            l.parse().map(|i| instructions.push(i))
+        };
+        if let Err(e) = parsed {
+            errors.push(e.at_line(idx + 1));
+        }
+    }
+    if fold_lines == 0 {
+        let line = input.lines().count() + 1;
+        let kind = ParseErrorKind::Expected("at least one fold instruction");
+        errors.push(AocError::parse(13, line, 1, kind));
+    }
+    let paper = Paper {
+        points,
+        instructions,
+    };
+    (paper, errors)
 }
 
+/// Parses `x,y` dots, a blank line, then `fold along x=...` instructions
 #[aoc_generator(day13)]
-fn parse(input: &str) -> Paper {
-    let input: Vec<&str> = input.split("\n\n").collect();
+pub fn parse(input: &str) -> Result<Paper, AocError> {
+    let input = &normalize(input);
+    first_error(read(input))
+}
 
-    let points = input[0].lines().map(|l| l.parse().unwrap()).collect();
-    let instructions = input[1].lines().map(|l| l.parse().unwrap()).collect();
+/// Whether `paper` could come from `parse`: dots and fold lines within bounds, and at least one
+/// fold
+fn valid(paper: &Paper) -> bool {
+    let bounded = |x: i32| (0..=MAX_COORDINATE).contains(&x);
+    let fold_bounded = |f: &FoldInstruction| match *f {
+        FoldInstruction::X(i) | FoldInstruction::Y(i) => bounded(i),
+    };
+    !paper.instructions.is_empty()
+        && paper.instructions.iter().all(fold_bounded)
+        && paper.points.iter().all(|p| bounded(p.x) && bounded(p.y))
+}
 
-    Paper {
-        points,
-        instructions,
-    }
+/// Every faulty dot or fold instruction
+pub fn lint(input: &str) -> Vec<AocError> {
+    read(input).1
 }
 
 #[aoc(day13, part1)]
-fn part1(input: &Paper) -> usize {
+pub fn part1(input: &Paper) -> Result<usize, AocError> {
+    let first = input
+        .instructions
+        .first()
+        .ok_or_else(|| AocError::no_solution(13, "there is no fold instruction"))?;
     let mut points = input.points.clone();
-    do_fold(&mut points, &input.instructions[0]);
-    points
+    fold_points(&mut points, first);
+    Ok(points
         .iter()
-        .filter_map(|p| {
-            if p.x >= 0 && p.y >= 0 {
-                Some((p.x, p.y))
-            } else {
-                None
-            }
-        })
+        .filter(|p| p.x >= 0 && p.y >= 0)
         .collect::<HashSet<_>>()
-        .len()
+        .len())
 }
 
 #[aoc(day13, part2)]
-fn part2(input: &Paper) -> String {
+pub fn part2(input: &Paper) -> String {
     let mut points = input.points.clone();
     input.instructions.iter().for_each(|instr| {
-        do_fold(&mut points, instr);
+        fold_points(&mut points, instr);
     });
     display_code(&points)
 }
 
-fn display_code(points: &Vec<Point>) -> String {
-    let (max_x, max_y) = points
-        .iter()
-        .fold((0, 0), |max, p| (max.0.max(p.x), max.1.max(p.y)));
-    let points = points.iter().map(|p| (p.x, p.y)).collect::<HashSet<_>>();
+/// Bounds of the dots, the sheet starting at the origin
+fn sheet_bounds(points: &[Point2<i32>]) -> Bounds<i32> {
+    Bounds::of(points.iter().copied().chain([Point2::default()])).expect("The origin is bounded")
+}
+
+/// Draws the dots, each line of the drawing starting with a line break
+pub fn display_code(points: &[Point2<i32>]) -> String {
+    let max = sheet_bounds(points).max;
+    let points = points.iter().collect::<HashSet<_>>();
     let mut output = String::new();
-    for y in 0..=max_y {
+    for y in 0..=max.y {
         output += "\n";
-        for x in 0..=max_x {
-            if points.contains(&(x, y)) {
+        for x in 0..=max.x {
+            if points.contains(&Point2::new(x, y)) {
                 output += "█";
             } else {
                 output += " ";
@@ -101,55 +174,134 @@ fn display_code(points: &Vec<Point>) -> String {
     output
 }
 
-fn do_fold(points: &mut Vec<Point>, fold: &FoldInstruction) {
+/// Moves the dots beyond the fold line onto the other half of the paper
+pub fn fold_points(points: &mut [Point2<i32>], fold: &FoldInstruction) {
     for p in points.iter_mut().filter(|p| match fold {
         FoldInstruction::X(i) => p.x > *i,
         FoldInstruction::Y(i) => p.y > *i,
     }) {
         match fold {
-            FoldInstruction::X(i) => p.x = i - (p.x - i),
-            FoldInstruction::Y(i) => p.y = i - (p.y - i),
+            FoldInstruction::X(i) => p.x = mirror(p.x, *i),
+            FoldInstruction::Y(i) => p.y = mirror(p.y, *i),
         }
     }
 }
 
-#[cfg(test)]
-pub mod tests {
-    use super::*;
+/// `x` mirrored across `line`, computed in `i64` as dots that aren't parsed may be anywhere. The
+/// result is below `line`, so it saturates at `i32::MIN`.
+fn mirror(x: i32, line: i32) -> i32 {
+    i32::try_from(2 * i64::from(line) - i64::from(x)).unwrap_or(i32::MIN)
+}
 
-    const INPUT: &'static str = "6,10
-0,14
-9,10
-0,3
-10,4
-4,11
-6,0
-6,12
-4,1
-0,13
-10,12
-3,4
-3,0
-8,4
-1,10
-2,14
-8,10
-9,0
-
-fold along y=7
-fold along x=5";
-
-    const OUTPUT_PART_2: &'static str = "
+/// Dots on the paper before and after each fold, drawn on the unfolded sheet
+impl Visualize for Paper {
+    fn frames(&self) -> Vec<Frame> {
+        let max = sheet_bounds(&self.points).max;
+        let frame = |points: &[Point2<i32>]| {
+            let mut sheet = Grid::from_fn(max.x as usize + 1, max.y as usize + 1, |_| 0);
+            points
+                .iter()
+                .filter(|p| p.x >= 0 && p.y >= 0)
+                .for_each(|p| sheet[Point2::new(p.x as usize, p.y as usize)] = 255);
+            Frame::Gray(sheet)
+        };
+        let mut points = self.points.clone();
+        let mut frames = vec![frame(&points)];
+        for fold in &self.instructions {
+            fold_points(&mut points, fold);
+            frames.push(frame(&points));
+        }
+        frames
+    }
+}
+
+const EXAMPLE: &str = include_str!("../examples/day13/example.txt");
+
+const EXAMPLE_PART_2: &str = "
 █████
 █   █
 █   █
 █   █
 █████";
 
+pub fn solver() -> impl Solver {
+    Solution {
+        day: 13,
+        title: "Transparent Origami",
+        examples: &[Example {
+            input: EXAMPLE,
+            part1: Some("17"),
+            part2: Some(EXAMPLE_PART_2),
+        }],
+        parse,
+        valid,
+        part1,
+        part2,
+        variants: vec![Variant {
+            name: oracle::VARIANT,
+            part1: Some(oracle::day13::part1),
+            part2: Some(oracle::day13::part2),
+        }],
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn should_work() -> Result<(), AocError> {
+        let data = parse(EXAMPLE)?;
+        assert_eq!(part1(&data)?, 17);
+        assert_eq!(part2(&data), EXAMPLE_PART_2);
+        Ok(())
+    }
+
+    #[test]
+    fn should_report_unknown_axis() {
+        let err = parse("6,10\n\nfold along z=7").err();
+        assert_eq!(
+            err,
+            Some(AocError::parse(
+                13,
+                3,
+                12,
+                ParseErrorKind::UnknownToken("z".to_string())
+            ))
+        );
+    }
+
+    #[test]
+    fn should_bound_coordinates() {
+        let err = parse("2000000000,0\n\nfold along x=1").err();
+        let kind = ParseErrorKind::Expected("a coordinate from 0 to 4095");
+        assert_eq!(err, Some(AocError::parse(13, 1, 1, kind)));
+        let err = parse("4095,0\n\nfold along x=-2000000000").err();
+        let kind = ParseErrorKind::Expected("a fold line from 0 to 4095");
+        assert_eq!(err, Some(AocError::parse(13, 3, 14, kind)));
+
+        // Dots built by hand aren't bounded
+        let mut points = [Point2::new(1, 0), Point2::new(i32::MAX, 1)];
+        fold_points(&mut points, &FoldInstruction::X(-1000000000));
+        assert_eq!(
+            points,
+            [Point2::new(-2000000001, 0), Point2::new(i32::MIN, 1)]
+        );
+    }
+
     #[test]
-    fn should_work() {
-        let data = parse(INPUT);
-        assert_eq!(part1(&data), 17);
-        assert_eq!(part2(&data), OUTPUT_PART_2);
+    fn should_report_missing_fold() {
+        let paper = Paper {
+            points: vec![Point2::new(0, 0)],
+            instructions: Vec::new(),
+        };
+        assert!(matches!(
+            part1(&paper),
+            Err(AocError::NoSolution { day: 13, .. })
+        ));
+        assert!(matches!(
+            oracle::day13::part1(&paper),
+            Err(AocError::NoSolution { day: 13, .. })
+        ));
     }
 }
@@ -1,90 +1,261 @@
-use itertools::Itertools;
-use std::collections::HashMap;
+use crate::arith;
+use crate::error::{column_of, first_error, split_pair, AocError, ParseErrorKind};
+use crate::input::normalize;
+#[cfg(feature = "std")]
+use crate::oracle;
+#[cfg(feature = "std")]
+use crate::solver::{Example, Solution, Solver, Variant};
+use crate::trace::event;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 
-struct Template {
-    init: String,
-    templates: HashMap<(char, char), char>,
+/// The polymer template and the pair insertion rules
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Template {
+    pub init: String,
+    pub templates: BTreeMap<(char, char), char>,
 }
 
-#[aoc_generator(day14)]
-fn parse(input: &str) -> Template {
-    let input: Vec<&str> = input.split("\n\n").collect();
-    let init = input[0].to_string();
-    let templates = input[1]
-        .lines()
-        .map(|t| {
-            let line: Vec<&str> = t.split(" -> ").collect();
-            let input: Vec<char> = line[0].chars().collect();
-            let output: char = line[1].chars().next().unwrap();
-            ((input[0], input[1]), output)
-        })
-        .collect();
-    Template { init, templates }
+impl Template {
+    /// Difference between the quantities of the most and least common elements after `steps`
+    /// of pair insertion
+    pub fn score_after(&self, steps: usize) -> Result<usize, AocError> {
+        check_rules(&self.init, &self.templates)?;
+        solve(&self.init, &self.templates, steps)
+    }
+}
+
+/// Parses a `AB -> C` insertion rule
+fn parse_rule(line: &str) -> Result<((char, char), char), AocError> {
+    let (pair, output) = split_pair(14, line, " -> ", "` -> `")?;
+    let column = column_of(line, output);
+    let pair: Vec<char> = pair.chars().collect();
+    let output: Vec<char> = output.chars().collect();
+    match (pair.as_slice(), output.as_slice()) {
+        (&[a, b], &[c]) => Ok(((a, b), c)),
+        (&[_, _], _) => Err(AocError::parse(
+            14,
+            1,
+            column,
+            ParseErrorKind::Expected("a single inserted element"),
+        )),
+        _ => Err(AocError::parse(
+            14,
+            1,
+            1,
+            ParseErrorKind::Expected("a pair of elements"),
+        )),
+    }
+}
+
+/// Same as `parse`, carrying on after problems so that every one of them is found
+fn read(input: &str) -> (Template, Vec<AocError>) {
+    let mut errors = Vec::new();
+    let mut lines = input.lines().enumerate();
+    let init = match lines.next() {
+        Some((_, init)) if init.chars().count() > 1 => init.to_string(),
+        Some((_, "")) | None => {
+            errors.push(AocError::parse(14, 1, 1, ParseErrorKind::Empty));
+            String::new()
+        }
+        Some(_) => {
+            let kind = ParseErrorKind::Expected("at least two elements");
+            errors.push(AocError::parse(14, 1, 2, kind));
+            String::new()
+        }
+    };
+    match lines.next() {
+        Some((_, "")) => {}
+        _ => {
+            let kind = ParseErrorKind::Expected("a blank line");
+            errors.push(AocError::parse(14, 2, 1, kind));
+        }
+    }
+    let mut templates = BTreeMap::new();
+    for (idx, l) in lines {
+        if l.is_empty() {
+            errors.push(AocError::parse(14, idx + 1, 1, ParseErrorKind::Empty));
+            continue;
+        }
+        match parse_rule(l) {
+            Ok((pair, inserted)) => {
+                templates.insert(pair, inserted);
+            }
+            Err(e) => errors.push(e.at_line(idx + 1)),
+        }
+    }
+    (Template { init, templates }, errors)
+}
+
+/// Parses the template, a blank line, then one `AB -> C` rule per line
+#[cfg_attr(feature = "std", aoc_generator(day14))]
+pub fn parse(input: &str) -> Result<Template, AocError> {
+    let input = &normalize(input);
+    first_error(read(input))
+}
+
+/// Every problem with the template or the rules
+pub fn lint(input: &str) -> Vec<AocError> {
+    read(input).1
+}
+
+#[cfg_attr(feature = "std", aoc(day14, part1))]
+pub fn part1(input: &Template) -> Result<usize, AocError> {
+    input.score_after(10)
+}
+
+#[cfg_attr(feature = "std", aoc(day14, part2))]
+pub fn part2(input: &Template) -> Result<usize, AocError> {
+    input.score_after(40)
+}
+
+/// Adjacent pairs of elements of the polymer
+fn pairs(input: &str) -> impl Iterator<Item = (char, char)> + '_ {
+    input.chars().zip(input.chars().skip(1))
 }
 
-#[aoc(day14, part1)]
-fn part1(input: &Template) -> usize {
-    solve(&input.init, &input.templates, 10)
+/// Makes sure every pair that can show up in the polymer has an insertion rule
+fn check_rules(input: &str, templates: &BTreeMap<(char, char), char>) -> Result<(), AocError> {
+    let mut open: Vec<(char, char)> = pairs(input).collect();
+    let mut seen: BTreeSet<(char, char)> = open.iter().copied().collect();
+    while let Some((a, b)) = open.pop() {
+        let c = *templates
+            .get(&(a, b))
+            .ok_or_else(|| AocError::no_solution(14, "a pair has no insertion rule"))?;
+        for pair in [(a, c), (c, b)] {
+            if seen.insert(pair) {
+                open.push(pair);
+            }
+        }
+    }
+    Ok(())
 }
 
-#[aoc(day14, part2)]
-fn part2(input: &Template) -> usize {
-    solve(&input.init, &input.templates, 40)
+fn solve(
+    input: &str,
+    templates: &BTreeMap<(char, char), char>,
+    step: usize,
+) -> Result<usize, AocError> {
+    let mut pair_counts: BTreeMap<(char, char), usize> = BTreeMap::new();
+    for pair in pairs(input) {
+        *pair_counts.entry(pair).or_default() += 1;
+    }
+    for _ in 0..step {
+        let mut new_pairs = BTreeMap::new();
+        for (&(a, b), &count) in &pair_counts {
+            let c = templates[&(a, b)];
+            for pair in [(a, c), (c, b)] {
+                let total = new_pairs.entry(pair).or_default();
+                *total = arith::add(14, *total, count)?;
+            }
+        }
+        // Saturating, as the length can outgrow a `usize` while the count of each pair fits
+        event!(
+            length = new_pairs
+                .values()
+                .fold(1usize, |total, &count| total.saturating_add(count)),
+            pairs = ?new_pairs
+        );
+        pair_counts = new_pairs;
+    }
+    let mut map: BTreeMap<char, usize> = BTreeMap::new();
+    for (&(a, _), &count) in &pair_counts {
+        let total = map.entry(a).or_default();
+        *total = arith::add(14, *total, count)?;
+    }
+    // Pairs overlap, so counting their first elements misses the last one of the polymer, which
+    // never changes
+    if let Some(last) = input.chars().last() {
+        let total = map.entry(last).or_default();
+        *total = arith::add(14, *total, 1)?;
+    }
+    Ok(map.values().max().unwrap_or(&0) - map.values().min().unwrap_or(&0))
 }
 
-fn solve(input: &str, templates: &HashMap<(char, char), char>, step: usize) -> usize {
-    let first = input.chars().tuple_windows().counts();
-    let maps = std::iter::successors(Some(first), |pairs: &HashMap<(char, char), usize>| {
-        let new_pairs = pairs
-            .iter()
-            .flat_map(|(&(a, b), &count)| {
-                [
-                    ((a, templates[&(a, b)]), count),
-                    ((templates[&(a, b)], b), count),
-                ]
-            })
-            .into_grouping_map()
-            .sum();
-        Some(new_pairs)
-    })
-    .skip(step)
-    .next()
-    .expect("Failed to compute");
-    let map = maps
-        .iter()
-        .map(|(&(a, _), &c)| (a, c))
-        .into_grouping_map()
-        .sum();
-    map.values().max().unwrap() - map.values().min().unwrap() + 1
+#[cfg(any(test, feature = "std"))]
+const EXAMPLE: &str = include_str!("../examples/day14/example.txt");
+
+#[cfg(feature = "std")]
+pub fn solver() -> impl Solver {
+    Solution {
+        day: 14,
+        title: "Extended Polymerization",
+        examples: &[Example {
+            input: EXAMPLE,
+            part1: Some("1588"),
+            part2: Some("2188189693529"),
+        }],
+        parse,
+        valid: |template| template.init.chars().count() > 1,
+        part1,
+        part2,
+        variants: vec![Variant {
+            name: oracle::VARIANT,
+            part1: Some(oracle::day14::part1),
+            part2: None,
+        }],
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
 
-    const INPUT: &'static str = "NNCB
-
-CH -> B
-HH -> N
-CB -> H
-NH -> C
-HB -> C
-HC -> B
-HN -> C
-NN -> C
-BH -> H
-NC -> B
-NB -> B
-BN -> B
-BB -> N
-BC -> B
-CC -> N
-CN -> C";
+    #[test]
+    fn should_work() -> Result<(), AocError> {
+        let data = parse(EXAMPLE)?;
+        assert_eq!(part1(&data)?, 1588);
+        assert_eq!(part2(&data)?, 2188189693529);
+        Ok(())
+    }
+
+    #[test]
+    fn should_report_missing_rules() -> Result<(), AocError> {
+        assert!(matches!(parse("N\n\nNN -> N"), Err(AocError::Parse { .. })));
+        let data = parse("NC\n\nNC -> B\nNB -> B")?;
+        assert!(matches!(
+            part1(&data),
+            Err(AocError::NoSolution { day: 14, .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn should_require_one_blank_line() {
+        let kind = || ParseErrorKind::Expected("a blank line");
+        assert_eq!(
+            parse("NN\nNN -> N").err(),
+            Some(AocError::parse(14, 2, 1, kind()))
+        );
+        assert_eq!(parse("NN").err(), Some(AocError::parse(14, 2, 1, kind())));
+        assert_eq!(
+            lint("NN\n\n\nNN -> N\n\nNC -> B"),
+            vec![
+                AocError::parse(14, 3, 1, ParseErrorKind::Empty),
+                AocError::parse(14, 5, 1, ParseErrorKind::Empty)
+            ]
+        );
+    }
+
+    #[cfg(feature = "checked-arith")]
+    #[test]
+    fn should_report_overflows() -> Result<(), AocError> {
+        // The polymer doubles in length at each step
+        let data = parse(EXAMPLE)?;
+        assert_eq!(data.score_after(55)?, 72_007_166_362_569_465);
+        assert_eq!(data.score_after(70), Err(AocError::Overflow(14)));
+        Ok(())
+    }
 
     #[test]
-    fn should_work() {
-        let data = parse(INPUT);
-        assert_eq!(part1(&data), 1588);
-        assert_eq!(part2(&data), 2188189693529);
+    fn should_score_empty_template() -> Result<(), AocError> {
+        let data = Template {
+            init: String::new(),
+            templates: BTreeMap::new(),
+        };
+        assert_eq!((part1(&data)?, part2(&data)?), (0, 0));
+        Ok(())
     }
 }
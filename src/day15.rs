@@ -1,105 +1,156 @@
-struct ChitonsMap {
-    chitons: Vec<Vec<i32>>,
-    width: i32,
-    height: i32,
+use crate::error::AocError;
+use crate::geom::Point2;
+use crate::grid::{Grid, Position};
+use crate::input::normalize;
+use crate::oracle;
+use crate::solver::{Example, Solution, Solver, Variant};
+use crate::viz::{Frame, Visualize};
+use pathfinding::directed::dijkstra::{build_path, dijkstra, dijkstra_all};
+use std::collections::HashSet;
+
+/// Parses one row of risk levels per line
+#[aoc_generator(day15)]
+pub fn parse(input: &str) -> Result<Grid<u8>, AocError> {
+    let input = &normalize(input);
+    Grid::parse_with(input, risk).map_err(|e| AocError::from_grid(15, e))
 }
 
-impl ChitonsMap {
-    pub fn neighbors(&self, pos: &(i32, i32)) -> impl Iterator<Item = ((i32, i32), i32)> + '_ {
-        [
-            (pos.0 - 1, pos.1),
-            (pos.0, pos.1 - 1),
-            (pos.0 + 1, pos.1),
-            (pos.0, pos.1 + 1),
-        ]
-        .into_iter()
-        .filter(|(x, y)| *x >= 0 && *x <= self.width && *y >= 0 && *y <= self.height)
-        .map(|(x, y)| ((x, y), self.chitons[y as usize][x as usize]))
-    }
+/// Every row with other cells than risk levels or a different width than the first one
+pub fn lint(input: &str) -> Vec<AocError> {
+    let errors = Grid::lint_with(input, risk).into_iter();
+    errors.map(|e| AocError::from_grid(15, e)).collect()
+}
 
-    pub fn neighbors_two(&self, pos: &(i32, i32)) -> impl Iterator<Item = ((i32, i32), i32)> + '_ {
-        [
-            (pos.0 - 1, pos.1),
-            (pos.0, pos.1 - 1),
-            (pos.0 + 1, pos.1),
-            (pos.0, pos.1 + 1),
-        ]
-        .into_iter()
-        .filter(|(x, y)| {
-            *x >= 0 && *x <= (self.width + 1) * 5 - 1 && *y >= 0 && *y <= (self.height + 1) * 5 - 1
-        })
-        .map(|(x, y)| {
-            let mapped_x = x % (self.width + 1);
-            let mapped_y = y % (self.height + 1);
-            let ix = x / (self.width + 1);
-            let iy = y / (self.height + 1);
-            let risk = self.chitons[mapped_y as usize][mapped_x as usize] + ix + iy;
-            let risk = risk % 10 + risk / 10;
-            ((x, y), risk)
-        })
-    }
+/// Risk levels go from 1 to 9
+fn risk(c: char) -> Option<u8> {
+    c.to_digit(10).filter(|&d| d > 0).map(|d| d as u8)
 }
 
-#[aoc_generator(day15)]
-fn parse(input: &str) -> ChitonsMap {
-    let chitons: Vec<Vec<i32>> = input
-        .lines()
-        .map(|l| {
-            l.chars()
-                .map(|c| c.to_digit(10).unwrap() as i32)
-                .collect::<Vec<_>>()
-        })
-        .collect();
-    let width = chitons[0].len() as i32 - 1;
-    let height = chitons.len() as i32 - 1;
-    ChitonsMap {
-        chitons,
-        width,
-        height,
-    }
+/// Builds the full cave: the input tile repeated `times` times in both directions,
+/// risk increasing by one per tile away from the top-left and wrapping from 9 to 1.
+pub fn expand(input: &Grid<u8>, times: usize) -> Grid<u8> {
+    let (width, height) = (input.width(), input.height());
+    Grid::from_fn(width * times, height * times, |Point2 { x, y }| {
+        let risk = input[Point2::new(x % width, y % height)] as usize + x / width + y / height;
+        ((risk - 1) % 9 + 1) as u8
+    })
 }
 
-#[aoc(day15, part1)]
-fn part1(input: &ChitonsMap) -> i32 {
-    let (_, cost) = pathfinding::directed::dijkstra::dijkstra(
-        &(0, 0),
-        |pos| input.neighbors(pos),
-        |(x, y)| *x == input.width && *y == input.height,
+/// Moves from `pos`, along with their risk
+fn moves(chitons: &Grid<u8>, pos: Position) -> impl Iterator<Item = (Position, u32)> + '_ {
+    chitons
+        .orthogonal_neighbors(pos)
+        .map(|p| (p, chitons[p] as u32))
+}
+
+/// Total risk of the safest path from the top left to the bottom right, the risk of the
+/// starting position not counting
+pub fn lowest_risk(chitons: &Grid<u8>) -> u32 {
+    let target = Point2::new(chitons.width() - 1, chitons.height() - 1);
+    let (_, cost) = dijkstra(
+        &Point2::new(0, 0),
+        |&pos| moves(chitons, pos),
+        |pos| *pos == target,
     )
     .expect("Failed to find path");
     cost
 }
 
+#[aoc(day15, part1)]
+pub fn part1(input: &Grid<u8>) -> u32 {
+    lowest_risk(input)
+}
+
 #[aoc(day15, part2)]
-fn part2(input: &ChitonsMap) -> i32 {
-    let (_, cost) = pathfinding::directed::dijkstra::dijkstra(
-        &(0, 0),
-        |pos| input.neighbors_two(pos),
-        |(x, y)| *x == (input.width + 1) * 5 - 1 && *y == (input.height + 1) * 5 - 1,
-    )
-    .expect("Failed to find path");
-    cost
+pub fn part2(input: &Grid<u8>) -> u32 {
+    lowest_risk(&expand(input, 5))
+}
+
+/// Cells reached by Dijkstra's algorithm in blue as the risk grows, then the safest path in red
+pub struct PathSearch<'a>(pub &'a Grid<u8>);
+
+const SEARCH_FRAMES: u32 = 100;
+
+impl Visualize for PathSearch<'_> {
+    fn frames(&self) -> Vec<Frame> {
+        let chitons = self.0;
+        let start = Point2::new(0, 0);
+        let target = Point2::new(chitons.width() - 1, chitons.height() - 1);
+        let costs = dijkstra_all(&start, |&pos| moves(chitons, pos));
+        let cost = |pos| match costs.get(&pos) {
+            Some(&(_, cost)) => cost,
+            None => 0,
+        };
+        let frame = |reached: u32, path: &HashSet<Position>| {
+            Frame::Color(Grid::from_fn(chitons.width(), chitons.height(), |pos| {
+                let risk = chitons[pos] * 20;
+                if path.contains(&pos) {
+                    [255, 60, 60]
+                } else if cost(pos) <= reached {
+                    [0, risk / 2, 75 + risk]
+                } else {
+                    [risk; 3]
+                }
+            }))
+        };
+
+        let max = chitons.positions().map(cost).max().unwrap_or(0);
+        let step = (max / SEARCH_FRAMES).max(1);
+        let mut frames: Vec<Frame> = (0..max)
+            .step_by(step as usize)
+            .map(|reached| frame(reached, &HashSet::new()))
+            .collect();
+        let path = build_path(&target, &costs).into_iter().collect();
+        frames.push(frame(max, &path));
+        frames
+    }
+}
+
+const EXAMPLE: &str = include_str!("../examples/day15/example.txt");
+
+pub fn solver() -> impl Solver {
+    Solution {
+        day: 15,
+        title: "Chiton",
+        examples: &[Example {
+            input: EXAMPLE,
+            part1: Some("40"),
+            part2: Some("315"),
+        }],
+        parse,
+        valid: |risks| !risks.is_empty() && risks.iter().all(|r| (1..=9).contains(r)),
+        part1,
+        part2,
+        variants: vec![Variant {
+            name: oracle::VARIANT,
+            part1: Some(oracle::day15::part1),
+            part2: Some(oracle::day15::part2),
+        }],
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
 
-    const INPUT: &'static str = "1163751742
-1381373672
-2136511328
-3694931569
-7463417111
-1319128137
-1359912421
-3125421639
-1293138521
-2311944581";
-
     #[test]
-    fn should_work() {
-        let data = parse(INPUT);
+    fn should_work() -> Result<(), AocError> {
+        let data = parse(EXAMPLE)?;
         assert_eq!(part1(&data), 40);
         assert_eq!(part2(&data), 315);
+        Ok(())
+    }
+
+    #[test]
+    fn should_reject_zero_risk() {
+        let err = parse("19\n01").err();
+        assert!(matches!(
+            err,
+            Some(AocError::Parse {
+                line: 2,
+                column: 1,
+                ..
+            })
+        ));
     }
 }
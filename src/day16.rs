@@ -1,39 +1,95 @@
 //! Handwritten parser for BITS protocol that could have been written with `nom`.
 //! Code is clear enough so I'm fine with that :)
 
-#[aoc_generator(day16)]
-fn parse(input: &str) -> String {
+use crate::arith;
+use crate::error::{lint_lines, AocError, ParseErrorKind};
+use crate::input::normalize;
+#[cfg(feature = "std")]
+use crate::oracle;
+#[cfg(feature = "std")]
+use crate::solver::{Example, Solution, Solver, Variant};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::str::FromStr;
+
+/// Turns the hexadecimal transmission into a string of `0` and `1`, which is what `Packet`
+/// parses
+#[cfg_attr(feature = "std", aoc_generator(day16))]
+pub fn parse(input: &str) -> Result<String, AocError> {
+    let input = &normalize(input);
     input
         .chars()
-        .map(|c| format!("{:04b}", c.to_digit(16).unwrap()))
+        .enumerate()
+        .map(|(idx, c)| match c.to_digit(16) {
+            Some(x) => Ok(format!("{:04b}", x)),
+            None => Err(AocError::parse(
+                16,
+                1,
+                idx + 1,
+                ParseErrorKind::UnexpectedChar(c),
+            )),
+        })
         .collect()
 }
 
-#[derive(Debug, PartialEq, Eq)]
-struct Packet {
-    version: u8, // Actually a u3
-    kind: PacketKind,
+/// Problems with the hexadecimal digits of the transmission, or with its outermost packet if
+/// there is none
+pub fn lint(input: &str) -> Vec<AocError> {
+    let mut lines = input.lines();
+    let mut errors = lint_lines(lines.next().unwrap_or(""), parse);
+    if lines.next().is_some() {
+        let kind = ParseErrorKind::Expected("a single line of hexadecimal digits");
+        errors.push(AocError::parse(16, 2, 1, kind));
+    }
+    if errors.is_empty() {
+        errors.extend(parse(input).and_then(|bits| Packet::parse(&bits)).err());
+    }
+    errors
+}
+
+/// Errors point at the hex digit holding the faulty bit
+fn error_at(bit: usize, kind: ParseErrorKind) -> AocError {
+    AocError::parse(16, 1, bit / 4 + 1, kind)
 }
 
-#[derive(Debug, PartialEq, Eq)]
-enum PacketKind {
+/// Reads `len` bits starting at bit `start`
+fn read_bits(input: &str, start: usize, len: usize) -> Result<u64, AocError> {
+    input
+        .get(start..start + len)
+        .and_then(|bits| u64::from_str_radix(bits, 2).ok())
+        .ok_or_else(|| error_at(start, ParseErrorKind::Expected("more packet bits")))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Packet {
+    pub version: u8, // Actually a u3
+    pub kind: PacketKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PacketKind {
     // I don't think we're going to need more than 64 bytes for the literals, sadly, this is unspecified by the problem
     Literal(u64),
     Operator(OperatorPacket),
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 // Weakyly-typed Operator Packet representation
 // `packets` is an unsafe collection of all sub-packets.
 // Effort could be made to represent this better by strong-typing using the OperatorKind enum.
 // Sadly, Aint nobody's got time for that.
-struct OperatorPacket {
-    kind: OperatorKind,
-    packets: Vec<Packet>,
+pub struct OperatorPacket {
+    pub kind: OperatorKind,
+    pub packets: Vec<Packet>,
 }
 
-#[derive(Debug, PartialEq, Eq)]
-enum OperatorKind {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OperatorKind {
     Sum,
     Product,
     Min,
@@ -43,18 +99,37 @@ enum OperatorKind {
     Equal,
 }
 
+impl OperatorKind {
+    /// What `packets` sub-packets lack for an operator of this kind to be evaluated, if anything
+    fn missing(self, packets: usize) -> Option<&'static str> {
+        match self {
+            OperatorKind::Greater | OperatorKind::Less | OperatorKind::Equal => {
+                (packets != 2).then_some("two sub-packets for a comparison")
+            }
+            _ => (packets == 0).then_some("at least one sub-packet"),
+        }
+    }
+}
+
 // Parsing methods : returns data + length of parsed element in bits
+// `start` is the absolute position of the element in the transmission, for error reporting.
 impl Packet {
-    fn parse(input: &str) -> (Self, usize) {
-        let version: u8 = u8::from_str_radix(&input[0..3], 2).unwrap();
-        let packet_type: u8 = u8::from_str_radix(&input[3..6], 2).unwrap();
+    /// Parses the outermost packet of a transmission in binary, returning it with its length in
+    /// bits. Trailing bits are ignored.
+    pub fn parse(input: &str) -> Result<(Self, usize), AocError> {
+        Packet::parse_at(input, 0)
+    }
+
+    fn parse_at(input: &str, start: usize) -> Result<(Self, usize), AocError> {
+        let version = read_bits(input, start, 3)? as u8;
+        let packet_type = read_bits(input, start + 3, 3)?;
         let (kind, size) = match packet_type {
             4 => {
-                let (x, size) = Packet::parse_literal(&input[6..]);
+                let (x, size) = Packet::parse_literal(input, start + 6)?;
                 (PacketKind::Literal(x), size)
             }
             x => {
-                let (packets, size) = Packet::parse_operator(&input[6..]);
+                let (packets, size) = Packet::parse_operator(input, start + 6)?;
                 let kind = match x {
                     0 => OperatorKind::Sum,
                     1 => OperatorKind::Product,
@@ -63,28 +138,31 @@ impl Packet {
                     5 => OperatorKind::Greater,
                     6 => OperatorKind::Less,
                     7 => OperatorKind::Equal,
-                    _ => panic!("Unsupported OperatorKind"), // Yeah yeah, this could use bettor error handling
+                    _ => unreachable!("packet types are 3 bits wide"),
                 };
+                if let Some(expected) = kind.missing(packets.len()) {
+                    return Err(error_at(start, ParseErrorKind::Expected(expected)));
+                }
                 (PacketKind::Operator(OperatorPacket { kind, packets }), size)
             }
         };
-        (Packet { version, kind }, size + 6)
+        Ok((Packet { version, kind }, size + 6))
     }
 
     // Takes 5 bits, checks first bit to know if we should keep reading, bitshift and then adds 4 bits
     // Conveniently, MSB comes first so we don't need no black magic :)
     // See test should_parse_literal
-    fn parse_literal(input: &str) -> (u64, usize) {
+    fn parse_literal(input: &str, start: usize) -> Result<(u64, usize), AocError> {
         let mut total: u64 = 0;
         for x in 0.. {
-            let idx = x * 5;
-            total = total << 4;
-            let parsed = u64::from_str_radix(&input[idx + 1..idx + 5], 2).unwrap();
-            if &input[idx..=idx] == "0" {
-                total += parsed;
-                return (total, (x + 1) * 5);
-            } else {
-                total += parsed;
+            let idx = start + x * 5;
+            total = arith::add(
+                16,
+                arith::mul(16, total, 16)?,
+                read_bits(input, idx + 1, 4)?,
+            )?;
+            if read_bits(input, idx, 1)? == 0 {
+                return Ok((total, (x + 1) * 5));
             }
         }
         unreachable!() // Actually unreachable because the for loop on top never ends
@@ -92,33 +170,33 @@ impl Packet {
 
     // Depending on the first bit, we want to read either 15 or 11 bits.
     // The parsing method of an operator ticket depends on that "mode".
-    fn parse_operator(input: &str) -> (Vec<Packet>, usize) {
+    fn parse_operator(input: &str, start: usize) -> Result<(Vec<Packet>, usize), AocError> {
         let mut packets = Vec::new();
         // See test should_parse_operator_0
-        if &input[0..=0] == "0" {
-            let total_length = usize::from_str_radix(&input[1..16], 2).unwrap();
+        if read_bits(input, start, 1)? == 0 {
+            let total_length = read_bits(input, start + 1, 15)? as usize;
             let mut idx = 0;
             while idx < total_length {
-                let (packet, size) = Packet::parse(&input[16 + idx..]);
+                let (packet, size) = Packet::parse_at(input, start + 16 + idx)?;
                 packets.push(packet);
                 idx += size;
             }
-            (packets, 16 + idx)
+            Ok((packets, 16 + idx))
         } else {
-            // See test should_parse_operator_0
-            let nb_packets = usize::from_str_radix(&input[1..12], 2).unwrap();
+            // See test should_parse_operator_1
+            let nb_packets = read_bits(input, start + 1, 11)?;
             let mut idx = 0;
             for _ in 0..nb_packets {
-                let (packet, size) = Packet::parse(&input[12 + idx..]);
+                let (packet, size) = Packet::parse_at(input, start + 12 + idx)?;
                 packets.push(packet);
                 idx += size;
             }
-            (packets, 12 + idx)
+            Ok((packets, 12 + idx))
         }
     }
 
-    // Trivial version calculation
-    fn version_sum(&self) -> u64 {
+    /// Sum of the versions of this packet and of all its sub-packets
+    pub fn version_sum(&self) -> u64 {
         match &self.kind {
             PacketKind::Literal(_) => self.version as u64,
             PacketKind::Operator(OperatorPacket { packets, .. }) => {
@@ -127,17 +205,27 @@ impl Packet {
         }
     }
 
-    // Evaluates the packet
-    fn value(&self) -> u64 {
-        match &self.kind {
+    /// Evaluates the expression of the packet
+    pub fn value(&self) -> Result<u64, AocError> {
+        Ok(match &self.kind {
             PacketKind::Literal(x) => *x,
             PacketKind::Operator(OperatorPacket { packets, kind }) => {
-                let values: Vec<u64> = packets.iter().map(Packet::value).collect();
+                let values: Vec<u64> = packets
+                    .iter()
+                    .map(Packet::value)
+                    .collect::<Result<_, _>>()?;
+                // Parsed packets always have them, but the fields are public
+                if kind.missing(values.len()).is_some() {
+                    return Err(AocError::no_solution(
+                        16,
+                        "an operator has the wrong number of sub-packets",
+                    ));
+                }
                 match kind {
-                    OperatorKind::Sum => values.iter().sum(),
-                    OperatorKind::Product => values.iter().product(),
-                    OperatorKind::Min => *values.iter().min().unwrap(),
-                    OperatorKind::Max => *values.iter().max().unwrap(),
+                    OperatorKind::Sum => arith::sum(16, values)?,
+                    OperatorKind::Product => arith::product(16, values)?,
+                    OperatorKind::Min => *values.iter().min().unwrap(), // safe because checked above
+                    OperatorKind::Max => *values.iter().max().unwrap(), // same
                     OperatorKind::Greater => {
                         if values[0] > values[1] {
                             1
@@ -161,34 +249,122 @@ impl Packet {
                     }
                 }
             }
-        }
+        })
+    }
+}
+
+/// Parses a hexadecimal transmission
+impl FromStr for Packet {
+    type Err = AocError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Ok(Packet::parse(&parse(input)?)?.0)
     }
 }
 
-#[aoc(day16, part1)]
-fn part1(input: &str) -> u64 {
-    let (packet, _size) = Packet::parse(input);
-    packet.version_sum()
+#[cfg_attr(feature = "std", aoc(day16, part1))]
+pub fn part1(input: &str) -> Result<u64, AocError> {
+    let (packet, _size) = Packet::parse(input)?;
+    Ok(packet.version_sum())
 }
 
-#[aoc(day16, part2)]
-fn part2(input: &str) -> u64 {
-    let (packet, _size) = Packet::parse(input);
+#[cfg_attr(feature = "std", aoc(day16, part2))]
+pub fn part2(input: &str) -> Result<u64, AocError> {
+    let (packet, _size) = Packet::parse(input)?;
     packet.value()
 }
 
+#[cfg(feature = "std")]
+pub fn solver() -> impl Solver {
+    Solution {
+        day: 16,
+        title: "Packet Decoder",
+        examples: &[
+            Example {
+                input: include_str!("../examples/day16/example1.txt"),
+                part1: Some("16"),
+                part2: None,
+            },
+            Example {
+                input: include_str!("../examples/day16/example2.txt"),
+                part1: Some("12"),
+                part2: None,
+            },
+            Example {
+                input: include_str!("../examples/day16/example3.txt"),
+                part1: Some("23"),
+                part2: None,
+            },
+            Example {
+                input: include_str!("../examples/day16/example4.txt"),
+                part1: Some("31"),
+                part2: None,
+            },
+            Example {
+                input: include_str!("../examples/day16/example5.txt"),
+                part1: None,
+                part2: Some("3"),
+            },
+            Example {
+                input: include_str!("../examples/day16/example6.txt"),
+                part1: None,
+                part2: Some("54"),
+            },
+            Example {
+                input: include_str!("../examples/day16/example7.txt"),
+                part1: None,
+                part2: Some("7"),
+            },
+            Example {
+                input: include_str!("../examples/day16/example8.txt"),
+                part1: None,
+                part2: Some("9"),
+            },
+            Example {
+                input: include_str!("../examples/day16/example9.txt"),
+                part1: None,
+                part2: Some("1"),
+            },
+            Example {
+                input: include_str!("../examples/day16/example10.txt"),
+                part1: None,
+                part2: Some("0"),
+            },
+            Example {
+                input: include_str!("../examples/day16/example11.txt"),
+                part1: None,
+                part2: Some("0"),
+            },
+            Example {
+                input: include_str!("../examples/day16/example12.txt"),
+                part1: None,
+                part2: Some("1"),
+            },
+        ],
+        parse,
+        valid: |bits| bits.chars().all(|c| c == '0' || c == '1'),
+        part1: |x| part1(x),
+        part2: |x| part2(x),
+        variants: vec![Variant {
+            name: oracle::VARIANT,
+            part1: Some(|x| oracle::day16::part1(x)),
+            part2: Some(|x| oracle::day16::part2(x)),
+        }],
+    }
+}
+
 #[cfg(test)]
 /// Fairly exhaustive testing of the BITS protocol!
 pub mod tests {
     use super::*;
 
-    const INPUT_LITERAL: &'static str = "D2FE28";
-    const INPUT_OPERATOR_0: &'static str = "38006F45291200";
-    const INPUT_OPERATOR_1: &'static str = "EE00D40C823060";
+    const INPUT_LITERAL: &str = "D2FE28";
+    const INPUT_OPERATOR_0: &str = "38006F45291200";
+    const INPUT_OPERATOR_1: &str = "EE00D40C823060";
 
     #[test]
-    fn should_parse_literal() {
-        let (packet, _size) = Packet::parse(&parse(INPUT_LITERAL));
+    fn should_parse_literal() -> Result<(), AocError> {
+        let (packet, _size) = Packet::parse(&parse(INPUT_LITERAL)?)?;
         assert_eq!(
             packet,
             Packet {
@@ -196,11 +372,12 @@ pub mod tests {
                 kind: PacketKind::Literal(2021)
             }
         );
+        Ok(())
     }
 
     #[test]
-    fn should_parse_operator_0() {
-        let (packet, _size) = Packet::parse(&parse(INPUT_OPERATOR_0));
+    fn should_parse_operator_0() -> Result<(), AocError> {
+        let (packet, _size) = Packet::parse(&parse(INPUT_OPERATOR_0)?)?;
         assert_eq!(
             packet,
             Packet {
@@ -220,11 +397,12 @@ pub mod tests {
                 })
             }
         );
+        Ok(())
     }
 
     #[test]
-    fn should_parse_operator_1() {
-        let (packet, _size) = Packet::parse(&parse(INPUT_OPERATOR_1));
+    fn should_parse_operator_1() -> Result<(), AocError> {
+        let (packet, _size) = Packet::parse(&parse(INPUT_OPERATOR_1)?)?;
         assert_eq!(
             packet,
             Packet {
@@ -248,25 +426,87 @@ pub mod tests {
                 })
             }
         );
+        Ok(())
+    }
+
+    #[test]
+    fn part1_should_work() -> Result<(), AocError> {
+        assert_eq!(part1(&parse("8A004A801A8002F478")?)?, 16);
+        assert_eq!(part1(&parse("620080001611562C8802118E34")?)?, 12);
+        assert_eq!(part1(&parse("C0015000016115A2E0802F182340")?)?, 23);
+        assert_eq!(part1(&parse("A0016C880162017C3686B18A3D4780")?)?, 31);
+        Ok(())
+    }
+
+    #[test]
+    fn part2_should_work() -> Result<(), AocError> {
+        assert_eq!(part2(&parse("C200B40A82")?)?, 3);
+        assert_eq!(part2(&parse("04005AC33890")?)?, 54);
+        assert_eq!(part2(&parse("880086C3E88112")?)?, 7);
+        assert_eq!(part2(&parse("CE00C43D881120")?)?, 9);
+        assert_eq!(part2(&parse("D8005AC2A8F0")?)?, 1);
+        assert_eq!(part2(&parse("F600BC2D8F")?)?, 0);
+        assert_eq!(part2(&parse("9C005AC2F8F0")?)?, 0);
+        assert_eq!(part2(&parse("9C0141080250320F1802104A08")?)?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn should_report_truncated_packets() -> Result<(), AocError> {
+        // Literal packet missing its last group
+        let err = part2(&parse("D2FE")?).err();
+        assert_eq!(
+            err,
+            Some(AocError::parse(
+                16,
+                1,
+                5,
+                ParseErrorKind::Expected("more packet bits")
+            ))
+        );
+        assert!(parse("D2FEX8").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn should_reject_operators_missing_sub_packets() {
+        let literal = Packet {
+            version: 0,
+            kind: PacketKind::Literal(1),
+        };
+        let operator = |kind, packets| Packet {
+            version: 0,
+            kind: PacketKind::Operator(OperatorPacket { kind, packets }),
+        };
+        let err = || AocError::no_solution(16, "an operator has the wrong number of sub-packets");
+        assert_eq!(operator(OperatorKind::Max, vec![]).value(), Err(err()));
+        let packets = vec![literal.clone(); 3];
+        assert_eq!(operator(OperatorKind::Less, packets).value(), Err(err()));
+        let packets = vec![literal.clone(), literal];
+        assert_eq!(operator(OperatorKind::Equal, packets).value(), Ok(1));
     }
 
+    #[cfg(not(feature = "checked-arith"))]
     #[test]
-    fn part1_should_work() {
-        assert_eq!(part1(&parse("8A004A801A8002F478")), 16);
-        assert_eq!(part1(&parse("620080001611562C8802118E34")), 12);
-        assert_eq!(part1(&parse("C0015000016115A2E0802F182340")), 23);
-        assert_eq!(part1(&parse("A0016C880162017C3686B18A3D4780")), 31);
+    fn should_wrap_around() -> Result<(), AocError> {
+        // Literal of 17 groups, 2^68 - 1
+        let data = parse("13FFFFFFFFFFFFFFFFFFFDE")?;
+        assert_eq!((part1(&data)?, part2(&data)?), (0, u64::MAX));
+        // Product of 2^32 by 2^32
+        assert_eq!(part2(&parse("0600848C2108421000918421084200")?)?, 0);
+        Ok(())
     }
 
+    #[cfg(feature = "checked-arith")]
     #[test]
-    fn part2_should_work() {
-        assert_eq!(part2(&parse("C200B40A82")), 3);
-        assert_eq!(part2(&parse("04005AC33890")), 54);
-        assert_eq!(part2(&parse("880086C3E88112")), 7);
-        assert_eq!(part2(&parse("CE00C43D881120")), 9);
-        assert_eq!(part2(&parse("D8005AC2A8F0")), 1);
-        assert_eq!(part2(&parse("F600BC2D8F")), 0);
-        assert_eq!(part2(&parse("9C005AC2F8F0")), 0);
-        assert_eq!(part2(&parse("9C0141080250320F1802104A08")), 1);
+    fn should_report_overflows() -> Result<(), AocError> {
+        // Literal of 17 groups, 2^68 - 1
+        let err = part2(&parse("13FFFFFFFFFFFFFFFFFFFDE")?).err();
+        assert_eq!(err, Some(AocError::Overflow(16)));
+        // Products of 2^32 by 2^31 and by 2^32
+        assert_eq!(part2(&parse("0600848C210842100098842108400")?)?, 1 << 63);
+        let err = part2(&parse("0600848C2108421000918421084200")?).err();
+        assert_eq!(err, Some(AocError::Overflow(16)));
+        Ok(())
     }
 }
@@ -1,105 +1,166 @@
+use crate::arith;
+use crate::error::{lint_lines, parse_field, split_pair, AocError, ParseErrorKind};
+use crate::input::normalize;
+use crate::oracle;
+use crate::solver::{Example, Solution, Solver, Variant};
+use crate::stream::for_each_line;
+use std::io::BufRead;
 use std::str::FromStr;
-use thiserror::Error;
 
-#[derive(Debug)]
-enum Instruction {
+/// A command of the submarine, moving it (or its aim) by an amount
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Instruction {
     Forward(i32),
     Down(i32),
     Up(i32),
 }
 
-#[derive(Debug, Error)]
-enum InstructionParseError {
-    #[error("Wrong format")]
-    WrongFormat(String),
-    #[error("Wrong instruction {0}")]
-    WrongInstruction(String),
-    #[error("Failed to parse int")]
-    ParseIntError(#[from] std::num::ParseIntError),
-}
-
 impl FromStr for Instruction {
-    type Err = InstructionParseError;
+    type Err = AocError;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let inst: Vec<&str> = input.split(" ").collect();
-        if inst.len() != 2 {
-            return Err(InstructionParseError::WrongFormat(input.to_string()));
-        }
-        match inst[0] {
-            "forward" => Ok(Instruction::Forward(inst[1].parse()?)),
-            "down" => Ok(Instruction::Down(inst[1].parse()?)),
-            "up" => Ok(Instruction::Up(inst[1].parse()?)),
-            x => Err(InstructionParseError::WrongInstruction(x.to_string())),
+        let (direction, amount) = split_pair(2, input, " ", "`<direction> <amount>`")?;
+        let amount = parse_field(2, input, amount)?;
+        match direction {
+            "forward" => Ok(Instruction::Forward(amount)),
+            "down" => Ok(Instruction::Down(amount)),
+            "up" => Ok(Instruction::Up(amount)),
+            x => Err(AocError::parse(
+                2,
+                1,
+                1,
+                ParseErrorKind::UnknownToken(x.to_string()),
+            )),
         }
     }
 }
 
+/// Parses one `<direction> <amount>` instruction per line
 #[aoc_generator(day2)]
-fn parse(input: &str) -> Result<Vec<Instruction>, InstructionParseError> {
-    input.lines().map(|x| Instruction::from_str(x)).collect()
+pub fn parse(input: &str) -> Result<Vec<Instruction>, AocError> {
+    let input = &normalize(input);
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, x)| Instruction::from_str(x).map_err(|e| e.at_line(idx + 1)))
+        .collect()
+}
+
+/// Every line that isn't an instruction
+pub fn lint(input: &str) -> Vec<AocError> {
+    lint_lines(input, Instruction::from_str)
 }
 
 #[aoc(day2, part1)]
-fn part1(instructions: &[Instruction]) -> i32 {
+pub fn part1(instructions: &[Instruction]) -> Result<i32, AocError> {
     let mut x = 0;
     let mut y = 0;
 
-    instructions.iter().for_each(|i| match i {
-        Instruction::Forward(xx) => x += xx,
-        Instruction::Up(yy) => y -= yy,
-        Instruction::Down(yy) => y += yy,
-    });
+    for i in instructions {
+        match *i {
+            Instruction::Forward(xx) => x = arith::add(2, x, xx)?,
+            Instruction::Up(yy) => y = arith::sub(2, y, yy)?,
+            Instruction::Down(yy) => y = arith::add(2, y, yy)?,
+        }
+    }
 
-    x * y
+    arith::mul(2, x, y)
 }
 
 #[aoc(day2, part2)]
-fn part2(instructions: &[Instruction]) -> i32 {
+pub fn part2(instructions: &[Instruction]) -> Result<i32, AocError> {
     let mut x = 0;
     let mut y = 0;
     let mut aim = 0;
 
-    instructions.iter().for_each(|i| match i {
-        Instruction::Forward(xx) => {
-            x += xx;
-            y += aim * xx;
-        }
-        Instruction::Up(yy) => {
-            aim -= yy;
+    for i in instructions {
+        match *i {
+            Instruction::Forward(xx) => {
+                x = arith::add(2, x, xx)?;
+                y = arith::add(2, y, arith::mul(2, aim, xx)?)?;
+            }
+            Instruction::Up(yy) => {
+                aim = arith::sub(2, aim, yy)?;
+            }
+            Instruction::Down(yy) => {
+                aim = arith::add(2, aim, yy)?;
+            }
         }
-        Instruction::Down(yy) => {
-            aim += yy;
+    }
+
+    arith::mul(2, x, y)
+}
+
+/// Both parts in a single pass. Positions are `i64` as long inputs quickly overflow an `i32`.
+pub fn stream(input: impl BufRead) -> Result<(i64, i64), AocError> {
+    let (mut x, mut depth, mut aim) = (0i64, 0i64, 0i64);
+    for_each_line(2, input, |line| {
+        match line.parse()? {
+            Instruction::Forward(xx) => {
+                x = arith::add(2, x, xx as i64)?;
+                depth = arith::add(2, depth, arith::mul(2, aim, xx as i64)?)?;
+            }
+            Instruction::Up(yy) => aim = arith::sub(2, aim, yy as i64)?,
+            Instruction::Down(yy) => aim = arith::add(2, aim, yy as i64)?,
         }
-    });
+        Ok(())
+    })?;
+    // Without aim, the depth is what the aim is with it
+    Ok((arith::mul(2, x, aim)?, arith::mul(2, x, depth)?))
+}
 
-    x * y
+const EXAMPLE: &str = include_str!("../examples/day2/example.txt");
+
+pub fn solver() -> impl Solver {
+    Solution {
+        day: 2,
+        title: "Dive!",
+        examples: &[Example {
+            input: EXAMPLE,
+            part1: Some("150"),
+            part2: Some("900"),
+        }],
+        parse,
+        valid: |_| true,
+        part1: |x| part1(x),
+        part2: |x| part2(x),
+        variants: vec![Variant {
+            name: oracle::VARIANT,
+            part1: Some(|x| Ok(oracle::day2::part1(x))),
+            part2: Some(|x| Ok(oracle::day2::part2(x))),
+        }],
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
 
-    const INPUT: &'static str = "forward 5
-down 5
-forward 8
-up 3
-down 8
-forward 2";
-
     #[test]
-    fn should_move_correctly() -> Result<(), InstructionParseError> {
-        let instructions = parse(INPUT)?;
-        assert_eq!(part1(&instructions), 150);
+    fn should_move_correctly() -> Result<(), AocError> {
+        let instructions = parse(EXAMPLE)?;
+        assert_eq!(part1(&instructions)?, 150);
 
         Ok(())
     }
 
     #[test]
-    fn should_aim_correctly() -> Result<(), InstructionParseError> {
-        let instructions = parse(INPUT)?;
-        assert_eq!(part2(&instructions), 900);
+    fn should_aim_correctly() -> Result<(), AocError> {
+        let instructions = parse(EXAMPLE)?;
+        assert_eq!(part2(&instructions)?, 900);
 
         Ok(())
     }
+
+    #[cfg(feature = "checked-arith")]
+    #[test]
+    fn should_report_overflows() -> Result<(), AocError> {
+        // Fine once in the `i64` of `stream`, too far for the `i32` of the parts
+        let input = "forward 50000\ndown 50000\nforward 1";
+        assert_eq!(part1(&parse(input)?), Err(AocError::Overflow(2)));
+        assert_eq!(part2(&parse(input)?), Err(AocError::Overflow(2)));
+        assert_eq!(stream(input.as_bytes())?, (2_500_050_000, 2_500_050_000));
+        Ok(())
+    }
 }
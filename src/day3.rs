@@ -1,123 +1,239 @@
-use std::simd::{u16x16, Mask, SimdElement};
+use crate::error::{lint_lines, AocError, ParseErrorKind};
+use crate::input::normalize;
+use crate::oracle;
+use crate::solver::{Example, Solution, Solver, Variant};
+use crate::trace::event;
 
+/// Parses one binary number per line
 #[aoc_generator(day3)]
-pub fn parse(input: &str) -> Result<Vec<u16>, std::num::ParseIntError> {
-    input.lines().map(|x| u16::from_str_radix(x, 2)).collect()
+pub fn parse(input: &str) -> Result<Vec<u16>, AocError> {
+    let input = &normalize(input);
+    if input.is_empty() {
+        return Err(AocError::parse(3, 1, 1, ParseErrorKind::Empty));
+    }
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, x)| parse_number(x).map_err(|e| e.at_line(idx + 1)))
+        .collect()
 }
 
-#[aoc(day3, part1)]
-pub fn part1(input: &[u16]) -> u32 {
-    let pows = u16x16::from([
-        32768, 16384, 8192, 4096, 2048, 1024, 512, 256, 128, 64, 32, 16, 8, 4, 2, 1,
-    ]);
-    let zeros = u16x16::splat(0);
+fn parse_number(line: &str) -> Result<u16, AocError> {
+    u16::from_str_radix(line, 2).map_err(|e| {
+        // Point at the first non-binary digit, if that's what went wrong
+        let column = line.find(|c| c != '0' && c != '1').unwrap_or(0) + 1;
+        AocError::parse(3, 1, column, e)
+    })
+}
 
-    //// dirty hack to cut irrelevant bits
-    let global_mask: u16 = input
-        .iter()
-        .max()
-        .expect("Failed to find max")
-        .next_power_of_two()
-        - 1;
-
-    let g_mask = gamma_mask(&input);
-    let epsilon_mask = !g_mask;
-
-    let gamma: u16 = g_mask
-        .select(pows, zeros)
-        .to_array()
-        .into_iter()
-        .sum::<u16>()
-        & global_mask;
-    let epsilon: u16 = epsilon_mask
-        .select(pows, zeros)
-        .to_array()
-        .into_iter()
-        .sum::<u16>()
-        & global_mask;
+/// Every line that isn't a binary number as wide as the first one
+pub fn lint(input: &str) -> Vec<AocError> {
+    if input.is_empty() {
+        return vec![AocError::parse(3, 1, 1, ParseErrorKind::Empty)];
+    }
+    let width = input.lines().next().map_or(0, str::len);
+    lint_lines(input, |l| {
+        parse_number(l)?;
+        if l.len() != width {
+            let kind = ParseErrorKind::RaggedRow {
+                expected: width,
+                found: l.len(),
+            };
+            return Err(AocError::parse(3, 1, width.min(l.len()) + 1, kind));
+        }
+        Ok(())
+    })
+}
 
+#[aoc(day3, part1)]
+pub fn part1(input: &[u16]) -> u32 {
+    let (gamma, epsilon) = power_rates(input);
     gamma as u32 * epsilon as u32
 }
 
 #[aoc(day3, part2)]
-fn part2(input: &Vec<u16>) -> u32 {
-    let oxygen_rate = find_rate(input.clone(), BitCriteria::MostCommon) as u32;
-    let co2_rate = find_rate(input.clone(), BitCriteria::LeastCommon) as u32;
-    oxygen_rate * co2_rate
+pub fn part2(input: &[u16]) -> Result<u32, AocError> {
+    let oxygen_rate = find_rate(input, BitCriteria::MostCommon)? as u32;
+    let co2_rate = find_rate(input, BitCriteria::LeastCommon)? as u32;
+    Ok(oxygen_rate * co2_rate)
+}
+
+/// Gamma and epsilon rates: the most and least common bits, as wide as the widest number.
+/// Panics if `input` is empty.
+pub fn power_rates(input: &[u16]) -> (u16, u16) {
+    // Cut the bits above the widest number
+    let global_mask = u16::MAX.checked_shr(16 - width(input)).unwrap_or(0);
+
+    let gamma = to_bits(gamma_mask(input)) & global_mask;
+    (gamma, !gamma & global_mask)
 }
 
-enum BitCriteria {
+/// Which bits are kept at each step of `find_rate`, ties going to ones for `MostCommon` and
+/// to zeros for `LeastCommon`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitCriteria {
     MostCommon,
     LeastCommon,
 }
 
-fn gamma_mask(input: &[u16]) -> Mask<<i16 as SimdElement>::Mask, 16> {
+/// Number of significant bits of the widest number
+/// (test = 5 bits, normal case = 12 bits)
+fn width(input: &[u16]) -> u32 {
+    let max = input.iter().max().expect("Failed to find max");
+    u16::BITS - max.leading_zeros()
+}
+
+/// Packs a mask (most significant bit first) back into a number
+fn to_bits(mask: [bool; 16]) -> u16 {
+    mask.iter().fold(0, |acc, &bit| (acc << 1) | bit as u16)
+}
+
+/// For each bit, most significant first: whether ones are at least as common as zeros
+fn gamma_mask(input: &[u16]) -> [bool; 16] {
+    #[cfg(feature = "simd")]
+    return simd_gamma_mask(input);
+    #[cfg(not(feature = "simd"))]
+    return scalar_gamma_mask(input);
+}
+
+#[cfg_attr(feature = "simd", allow(dead_code))]
+fn scalar_gamma_mask(input: &[u16]) -> [bool; 16] {
+    let mut totals = [0usize; 16];
+    input.iter().for_each(|x| {
+        totals
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, total)| *total += (x >> (15 - i) & 1) as usize);
+    });
+    totals.map(|total| total >= input.len() - total)
+}
+
+#[cfg(feature = "simd")]
+fn simd_gamma_mask(input: &[u16]) -> [bool; 16] {
+    use std::simd::{cmp::SimdPartialOrd, u16x16, Select};
+
     let pows = u16x16::from([
         32768, 16384, 8192, 4096, 2048, 1024, 512, 256, 128, 64, 32, 16, 8, 4, 2, 1,
     ]);
     let zeros = u16x16::splat(0);
     let ones = u16x16::splat(1);
 
-    let mut total = u16x16::splat(0);
-    input.iter().for_each(|x| {
-        let x = u16x16::splat(*x);
-        let and = pows & x;
-        let mask = and.lanes_gt(zeros);
-        let add = mask.select(ones, zeros);
-        total += add;
-    });
-    let other_count = u16x16::splat(input.len() as u16) - total;
-    total.lanes_ge(other_count)
+    let mut totals = [0usize; 16];
+    // Lanes can only count up to `u16::MAX`, so they're emptied into `totals` after each chunk
+    for chunk in input.chunks(u16::MAX as usize) {
+        let mut total = u16x16::splat(0);
+        chunk.iter().for_each(|x| {
+            let x = u16x16::splat(*x);
+            let and = pows & x;
+            let mask = and.simd_gt(zeros);
+            let add = mask.select(ones, zeros);
+            total += add;
+        });
+        totals
+            .iter_mut()
+            .zip(total.to_array())
+            .for_each(|(sum, count)| *sum += count as usize);
+    }
+    totals.map(|total| total >= input.len() - total)
 }
 
-fn find_rate(mut input: Vec<u16>, criteria: BitCriteria) -> u16 {
-    // dirty hack to get the bit to start with.
-    // Allows this to work in normal context and tests.
-    // (test = 5 bits, normal case = 12 bits)
-    let mut bit = input
-        .iter()
-        .max()
-        .expect("Failed to find max")
-        .next_power_of_two()
-        .log2() as usize;
+/// Filters numbers bit by bit, most significant first, until one remains: the oxygen generator
+/// rating for `MostCommon` and the CO2 scrubber rating for `LeastCommon`.
+/// Panics if `input` is empty.
+pub fn find_rate(input: &[u16], criteria: BitCriteria) -> Result<u16, AocError> {
+    let mut input = input.to_vec();
+    let mut bit = width(&input) as usize;
 
     loop {
         if input.len() == 1 {
-            return input[0];
+            return Ok(input[0]);
+        }
+        if bit == 0 {
+            return Err(AocError::no_solution(3, "the report has duplicate numbers"));
         }
         bit -= 1;
-        let g_mask = gamma_mask(&input).to_array();
+        let g_mask = gamma_mask(&input);
         let target_bit = match criteria {
             BitCriteria::MostCommon => g_mask[15 - bit],
             BitCriteria::LeastCommon => !g_mask[15 - bit],
         };
-        let mask = 2u16.pow(bit as u32);
+        let mask = 1u16 << bit;
         input.retain(|x| (x & mask > 0) == target_bit);
+        event!(bit, candidates = input.len(), ?criteria, "filtered");
+    }
+}
+
+const EXAMPLE: &str = include_str!("../examples/day3/example.txt");
+
+pub fn solver() -> impl Solver {
+    Solution {
+        day: 3,
+        title: "Binary Diagnostic",
+        examples: &[Example {
+            input: EXAMPLE,
+            part1: Some("198"),
+            part2: Some("230"),
+        }],
+        parse,
+        valid: |numbers| !numbers.is_empty(),
+        part1: |x| part1(x),
+        part2: |x| part2(x),
+        variants: vec![Variant {
+            name: oracle::VARIANT,
+            part1: Some(|x| oracle::day3::part1(x)),
+            part2: Some(|x| oracle::day3::part2(x)),
+        }],
     }
 }
 
 #[cfg(test)]
 pub mod tests {
-    use super::{parse, part1, part2};
-
-    const INPUT: &'static str = "00100
-11110
-10110
-10111
-10101
-01111
-00111
-11100
-10000
-11001
-00010
-01010";
+    use super::*;
+    use crate::error::AocError;
 
     #[test]
-    fn should_work() -> Result<(), std::num::ParseIntError> {
-        let input = parse(INPUT)?;
+    fn should_work() -> Result<(), AocError> {
+        let input = parse(EXAMPLE)?;
         assert_eq!(part1(&input), 198);
-        assert_eq!(part2(&input), 230);
+        assert_eq!(part2(&input)?, 230);
+        Ok(())
+    }
+
+    #[test]
+    fn should_handle_powers_of_two() -> Result<(), AocError> {
+        // The widest number is a power of two, so its top bit must still count
+        let input = parse("10000\n00001\n00011")?;
+        assert_eq!(part1(&input), 30);
+        Ok(())
+    }
+
+    #[test]
+    fn should_report_duplicates() -> Result<(), AocError> {
+        let input = parse("011\n011\n100")?;
+        assert!(matches!(
+            part2(&input),
+            Err(AocError::NoSolution { day: 3, .. })
+        ));
+        assert!(parse("").is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn simd_and_scalar_should_agree() -> Result<(), AocError> {
+        for seed in 0..20 {
+            let input = parse(&crate::gen::day3(seed, 12, 1000))?;
+            assert_eq!(simd_gamma_mask(&input), scalar_gamma_mask(&input));
+            for len in [1, 2, 3, 17] {
+                let slice = &input[..len];
+                assert_eq!(simd_gamma_mask(slice), scalar_gamma_mask(slice));
+            }
+        }
+        // More ones than a `u16` lane counts
+        let mut input = vec![1u16; 70_000];
+        input.extend(vec![0; 60_000]);
+        assert_eq!(simd_gamma_mask(&input), scalar_gamma_mask(&input));
+        assert!(simd_gamma_mask(&input)[15]);
         Ok(())
     }
 }
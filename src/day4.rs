@@ -1,18 +1,29 @@
-use std::{num::ParseIntError, str::FromStr};
+use crate::arith;
+use crate::error::{first_error, parse_field, AocError, ParseErrorKind};
+use crate::input::normalize;
+use crate::oracle;
+use crate::solver::{Example, Solution, Solver, Variant};
+use crate::trace::event;
+use std::str::FromStr;
 
-#[derive(Debug)]
+/// Numbers in the order they're drawn, and the boards playing
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BingoInfo {
     pub numbers: Vec<u32>,
     pub boards: Vec<BingoBoard>,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BingoBoard {
+    /// 5 rows of 5 numbers, `None` once they are marked
     pub rows: Vec<Vec<Option<u32>>>,
     pub winner: bool,
 }
 
 impl BingoBoard {
+    /// Marks `number`, updating `winner`
     pub fn call(&mut self, number: u32) {
         for r in &mut self.rows {
             for v in r.iter_mut() {
@@ -37,141 +48,271 @@ impl BingoBoard {
         self.winner = is_winner;
     }
 
-    pub fn columns(&self) -> impl Iterator<Item = [Option<u32>; 5]> + '_ {
-        (0..5).map(|i| {
-            [
-                self.rows[0][i],
-                self.rows[1][i],
-                self.rows[2][i],
-                self.rows[3][i],
-                self.rows[4][i],
-            ]
-        })
+    /// Columns of the board, left to right. Rows too short for a column are left out of it.
+    pub fn columns(&self) -> impl Iterator<Item = Vec<Option<u32>>> + '_ {
+        let width = self.rows.iter().map(Vec::len).max().unwrap_or(0);
+        (0..width).map(|i| self.rows.iter().filter_map(|r| r.get(i).copied()).collect())
     }
 
-    pub fn calc_score(&self) -> u32 {
-        self.rows
-            .iter()
-            .map(|r| r.iter().filter_map(|x| *x).sum::<u32>())
-            .sum()
+    /// Sum of the unmarked numbers
+    pub fn calc_score(&self) -> Result<u32, AocError> {
+        arith::sum(4, self.rows.iter().flatten().filter_map(|x| *x))
     }
 }
 
-impl FromStr for BingoBoard {
-    type Err = ParseIntError;
+/// Parses a row of 5 numbers
+fn parse_row(line: &str) -> Result<Vec<Option<u32>>, AocError> {
+    let row: Vec<Option<u32>> = line
+        .split(' ')
+        // yes, clippy will complain about .filter(..).map(..) but this makes it better
+        // for error handling
+        .filter(|x| !x.is_empty())
+        .map(|x| parse_field(4, line, x).map(Some))
+        .collect::<Result<_, _>>()?;
+    if row.len() != 5 {
+        return Err(AocError::parse(
+            4,
+            1,
+            line.len() + 1,
+            ParseErrorKind::Expected("5 numbers per row"),
+        ));
+    }
+    Ok(row)
+}
 
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let rows: Vec<Vec<Option<u32>>> = input
-            .lines()
-            .map(|l| {
-                l.split(" ")
-                    // yes, clippy will complain about .filter(..).map(..) but this makes it better
-                    // for error handling
-                    .filter(|x| !x.is_empty())
-                    .map(|x| Some(x.parse().unwrap()))
-                    .collect()
-            })
-            .collect();
-        Ok(BingoBoard {
-            rows,
-            winner: false,
-        })
+/// Parses a 5x5 board, carrying on after faulty rows so that every problem is found.
+/// Errors are reported relative to the first line of the board.
+fn read_board(input: &str) -> (BingoBoard, Vec<AocError>) {
+    let mut errors = Vec::new();
+    let mut rows = Vec::new();
+    for (idx, l) in input.lines().enumerate() {
+        match parse_row(l) {
+            Ok(row) => rows.push(row),
+            Err(e) => errors.push(e.at_line(idx + 1)),
+        }
     }
+    let count = input.lines().count();
+    if count != 5 {
+        let kind = ParseErrorKind::Expected("5 rows per board");
+        errors.push(AocError::parse(4, count + 1, 1, kind));
+    }
+    let board = BingoBoard {
+        rows,
+        winner: false,
+    };
+    (board, errors)
 }
 
-#[aoc_generator(day4)]
-fn parse(input: &str) -> Result<BingoInfo, ParseIntError> {
-    let lines: Vec<&str> = input.split("\n\n").collect();
+/// Whether `bingo` could come from `parse`: 5x5 boards with no number marked yet
+fn valid(bingo: &BingoInfo) -> bool {
+    bingo.boards.iter().all(|b| {
+        !b.winner
+            && b.rows.len() == 5
+            && b.rows
+                .iter()
+                .all(|r| r.len() == 5 && r.iter().all(Option::is_some))
+    })
+}
 
-    let numbers: Vec<u32> = lines[0]
-        .split(",")
-        .map(|x| x.parse())
-        .collect::<Result<_, _>>()?;
-    let boards: Vec<BingoBoard> = lines
-        .iter()
-        .skip(1)
-        .map(|x| x.parse())
-        .collect::<Result<_, _>>()?;
+/// Parses a 5x5 board. Errors are reported relative to the first line of the board.
+impl FromStr for BingoBoard {
+    type Err = AocError;
 
-    Ok(BingoInfo { numbers, boards })
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        first_error(read_board(input))
+    }
 }
 
-#[aoc(day4, part1)]
-fn part1(input: &BingoInfo) -> u32 {
-    let mut boards = input.boards.clone();
-
-    for n in &input.numbers {
-        for b in &mut boards {
-            b.call(*n);
-            if b.winner {
-                return b.calc_score() * n;
+impl BingoInfo {
+    /// Index and final score (sum of its unmarked numbers times the last number drawn) of each
+    /// board, in the order they win. Boards that never win are left out.
+    pub fn winning_scores(&self) -> Result<Vec<(usize, u32)>, AocError> {
+        let mut boards = self.boards.clone();
+        let mut scores = Vec::new();
+        for n in &self.numbers {
+            for (board, b) in boards.iter_mut().enumerate() {
+                if b.winner {
+                    continue;
+                }
+                b.call(*n);
+                if b.winner {
+                    let score = arith::mul(4, b.calc_score()?, *n)?;
+                    event!(board, number = n, score, "board wins");
+                    scores.push((board, score));
+                }
+            }
+            if scores.len() == boards.len() {
+                break;
             }
         }
+        Ok(scores)
     }
-
-    panic!("should have returned a score");
 }
 
-#[aoc(day4, part2)]
-fn part2(input: &BingoInfo) -> u32 {
-    let mut boards = input.boards.clone();
-    let mut remaining_boards = input.boards.len();
-
-    for n in &input.numbers {
-        for b in &mut boards {
-            if b.winner {
-                continue;
-            }
-            b.call(*n);
-            if b.winner {
-                remaining_boards -= 1;
-                if remaining_boards == 0 {
-                    return b.calc_score() * n;
-                }
+/// Parses the drawn numbers, then boards separated by blank lines, carrying on after problems
+/// so that every one of them is found
+fn read(input: &str) -> (BingoInfo, Vec<AocError>) {
+    let mut errors = Vec::new();
+    let first_line = input.lines().next().unwrap_or("");
+    let numbers: Vec<u32> = first_line
+        .split(',')
+        .filter_map(|x| {
+            parse_field(4, first_line, x)
+                .map_err(|e| errors.push(e))
+                .ok()
+        })
+        .collect();
+    // Boards are blocks of lines separated by blank lines, each starting at `start` (1-based)
+    let mut boards = Vec::new();
+    let mut add_board = |block: &str, start: usize| {
+        let (board, board_errors) = read_board(block);
+        errors.extend(board_errors.into_iter().map(|e| e.at_line(start)));
+        boards.push(board);
+    };
+    let mut block = String::new();
+    let mut start = 0;
+    for (idx, line) in input.lines().enumerate().skip(1) {
+        if line.trim().is_empty() {
+            if !block.is_empty() {
+                add_board(&block, start);
+                block.clear();
             }
+            continue;
         }
+        if block.is_empty() {
+            start = idx + 1;
+        }
+        block += line;
+        block += "\n";
+    }
+    if !block.is_empty() {
+        add_board(&block, start);
+    }
+    if boards.is_empty() {
+        let line = input.lines().count() + 1;
+        let kind = ParseErrorKind::Expected("at least one board");
+        errors.push(AocError::parse(4, line, 1, kind));
     }
+    (BingoInfo { numbers, boards }, errors)
+}
 
-    panic!("should have returned a score");
+/// Parses the drawn numbers, then boards separated by blank lines
+#[aoc_generator(day4)]
+pub fn parse(input: &str) -> Result<BingoInfo, AocError> {
+    let input = &normalize(input);
+    first_error(read(input))
 }
 
-#[cfg(test)]
-pub mod tests {
-    use super::*;
-    use std::num::ParseIntError;
+/// Every problem with the drawn numbers and the boards
+pub fn lint(input: &str) -> Vec<AocError> {
+    read(input).1
+}
 
-    const INPUT: &'static str =
-        "7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+#[aoc(day4, part1)]
+pub fn part1(input: &BingoInfo) -> Result<u32, AocError> {
+    input
+        .winning_scores()?
+        .first()
+        .map(|&(_, score)| score)
+        .ok_or_else(|| AocError::no_solution(4, "no board wins"))
+}
 
-22 13 17 11  0
- 8  2 23  4 24
-21  9 14 16  7
- 6 10  3 18  5
- 1 12 20 15 19
+#[aoc(day4, part2)]
+pub fn part2(input: &BingoInfo) -> Result<u32, AocError> {
+    let scores = input.winning_scores()?;
+    match scores.last() {
+        Some(&(_, score)) if scores.len() == input.boards.len() => Ok(score),
+        _ => Err(AocError::no_solution(4, "not every board wins")),
+    }
+}
 
- 3 15  0  2 22
- 9 18 13 17  5
-19  8  7 25 23
-20 11 10 24  4
-14 21 16 12  6
+const EXAMPLE: &str = include_str!("../examples/day4/example.txt");
 
-14 21 17 24  4
-10 16 15  9 19
-18  8 23 26 20
-22 11 13  6  5
- 2  0 12  3  7";
+pub fn solver() -> impl Solver {
+    Solution {
+        day: 4,
+        title: "Giant Squid",
+        examples: &[Example {
+            input: EXAMPLE,
+            part1: Some("4512"),
+            part2: Some("1924"),
+        }],
+        parse,
+        valid,
+        part1,
+        part2,
+        variants: vec![Variant {
+            name: oracle::VARIANT,
+            part1: Some(oracle::day4::part1),
+            part2: Some(oracle::day4::part2),
+        }],
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
 
     #[test]
-    fn should_parse() -> Result<(), ParseIntError> {
-        let _bingo_info: BingoInfo = parse(INPUT)?;
+    fn should_parse() -> Result<(), AocError> {
+        let _bingo_info: BingoInfo = parse(EXAMPLE)?;
         Ok(())
     }
 
     #[test]
-    fn should_solve() -> Result<(), ParseIntError> {
-        let bingo_info = parse(INPUT)?;
-        assert_eq!(part1(&bingo_info), 4512);
-        assert_eq!(part2(&bingo_info), 1924);
+    fn should_solve() -> Result<(), AocError> {
+        let bingo_info = parse(EXAMPLE)?;
+        assert_eq!(part1(&bingo_info)?, 4512);
+        assert_eq!(part2(&bingo_info)?, 1924);
         Ok(())
     }
+
+    #[test]
+    fn should_report_incomplete_board() {
+        let input = EXAMPLE.replacen("21  9 14 16  7", "21  9 14 16", 1);
+        assert_eq!(
+            parse(&input).err(),
+            Some(AocError::parse(
+                4,
+                5,
+                12,
+                ParseErrorKind::Expected("5 numbers per row")
+            ))
+        );
+    }
+
+    #[test]
+    fn should_call_any_board_shape() {
+        let mut board = BingoBoard {
+            rows: vec![vec![Some(1), Some(2)], vec![Some(3), Some(4), Some(5)]],
+            winner: false,
+        };
+        board.call(1);
+        assert!(!board.winner);
+        board.call(3);
+        assert!(board.winner);
+
+        let mut board = BingoBoard {
+            rows: Vec::new(),
+            winner: false,
+        };
+        board.call(1);
+        assert!(!board.winner);
+    }
+
+    #[test]
+    fn should_report_overflows() {
+        let bingo = BingoInfo {
+            numbers: vec![u32::MAX],
+            boards: vec![BingoBoard {
+                rows: vec![vec![Some(u32::MAX), Some(2)]],
+                winner: false,
+            }],
+        };
+        #[cfg(feature = "checked-arith")]
+        assert_eq!(part1(&bingo), Err(AocError::Overflow(4)));
+        #[cfg(not(feature = "checked-arith"))]
+        assert_eq!(part1(&bingo), Ok(u32::MAX - 1));
+        assert_eq!(part1(&bingo), oracle::day4::part1(&bingo));
+    }
 }
@@ -1,130 +1,174 @@
+use crate::error::{column_of, lint_lines, parse_bounded, split_pair, AocError, ParseErrorKind};
+use crate::geom::{Point2, Segment};
+use crate::input::normalize;
+use crate::oracle;
+use crate::solver::{Example, Solution, Solver, Variant};
+use crate::stream::for_each_line;
 use std::collections::HashMap;
-use std::{num::ParseIntError, str::FromStr};
+use std::io::BufRead;
 
-#[derive(Debug, Clone)]
-struct LineSegment {
-    pub p1: (i32, i32),
-    pub p2: (i32, i32),
-}
+/// Largest coordinate of a vent, the work growing with the length of the lines
+const MAX_COORDINATE: i32 = 9999;
 
-impl LineSegment {
-    fn is_horizontal_or_vertical(&self) -> bool {
-        self.p1.0 == self.p2.0 || self.p1.1 == self.p2.1
+/// Counts the points covered by a line of vents in `lines_map`, if it is horizontal, vertical
+/// or diagonal
+pub fn cover(segment: &Segment<i32>, lines_map: &mut HashMap<Point2<i32>, usize>) {
+    if is_horizontal_or_vertical(segment) || segment.is_diagonal() {
+        for p in segment.points() {
+            *lines_map.entry(p).or_insert(0) += 1;
+        }
     }
+}
 
-    fn is_diagonal(&self) -> bool {
-        let abs_x = (self.p1.0 - self.p2.0).abs();
-        let abs_y = (self.p1.1 - self.p2.1).abs();
-        abs_x == abs_y
-    }
+pub fn is_horizontal_or_vertical(segment: &Segment<i32>) -> bool {
+    segment.is_horizontal() || segment.is_vertical()
 }
 
-impl FromStr for LineSegment {
-    type Err = ParseIntError;
+/// Number of points covered at least twice
+pub fn overlaps(lines_map: &HashMap<Point2<i32>, usize>) -> usize {
+    lines_map.values().filter(|x| **x >= 2).count()
+}
 
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let points: Vec<&str> = input.split(" -> ").collect();
+/// Parses `x,y` where `point` is a subslice of `line`.
+fn parse_point(line: &str, point: &str) -> Result<Point2<i32>, AocError> {
+    let (x, y) = point.split_once(',').ok_or_else(|| {
+        let column = column_of(line, point) + point.len();
+        AocError::parse(5, 1, column, ParseErrorKind::Expected("`,`"))
+    })?;
+    let coordinate = |field| {
+        parse_bounded(
+            5,
+            line,
+            field,
+            0..=MAX_COORDINATE,
+            "a coordinate from 0 to 9999",
+        )
+    };
+    Ok(Point2::new(coordinate(x)?, coordinate(y)?))
+}
 
-        let p1: Vec<i32> = points[0]
-            .split(",")
-            .map(|x| x.parse())
-            .collect::<Result<_, _>>()?;
-        let p1 = (p1[0], p1[1]);
+/// Parses a `x1,y1 -> x2,y2` line of vents
+fn parse_segment(line: &str) -> Result<Segment<i32>, AocError> {
+    let (start, end) = split_pair(5, line, " -> ", "` -> `")?;
+    Ok(Segment::new(
+        parse_point(line, start)?,
+        parse_point(line, end)?,
+    ))
+}
 
-        let p2: Vec<i32> = points[1]
-            .split(",")
-            .map(|x| x.parse())
-            .collect::<Result<_, _>>()?;
-        let p2 = (p2[0], p2[1]);
+/// Parses one `x1,y1 -> x2,y2` segment per line
+#[aoc_generator(day5)]
+pub fn parse(input: &str) -> Result<Vec<Segment<i32>>, AocError> {
+    let input = &normalize(input);
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, x)| parse_segment(x).map_err(|e| e.at_line(idx + 1)))
+        .collect()
+}
 
-        Ok(LineSegment { p1, p2 })
-    }
+/// Whether `segments` could come from `parse`, their ends being within bounds
+fn valid(segments: &[Segment<i32>]) -> bool {
+    let bounded =
+        |p: Point2<i32>| (0..=MAX_COORDINATE).contains(&p.x) && (0..=MAX_COORDINATE).contains(&p.y);
+    segments.iter().all(|s| bounded(s.start) && bounded(s.end))
 }
 
-#[aoc_generator(day5)]
-fn parse(input: &str) -> Result<Vec<LineSegment>, ParseIntError> {
-    input.lines().map(|x| x.parse()).collect()
+/// Every line that isn't a `x1,y1 -> x2,y2` segment
+pub fn lint(input: &str) -> Vec<AocError> {
+    lint_lines(input, parse_segment)
 }
 
 #[aoc(day5, part1)]
-fn part1(input: &[LineSegment]) -> usize {
-    let mut lines_map: HashMap<(i32, i32), usize> = HashMap::default();
+pub fn part1(input: &[Segment<i32>]) -> usize {
+    let mut lines_map: HashMap<Point2<i32>, usize> = HashMap::default();
     input
         .iter()
-        .filter(|s| s.is_horizontal_or_vertical())
-        .for_each(|s| {
-            if s.is_horizontal_or_vertical() {
-                let min_x = s.p1.0.min(s.p2.0);
-                let max_x = s.p1.0.max(s.p2.0);
-                let min_y = s.p1.1.min(s.p2.1);
-                let max_y = s.p1.1.max(s.p2.1);
-                for x in min_x..=max_x {
-                    for y in min_y..=max_y {
-                        let entry = lines_map.entry((x, y)).or_insert(0);
-                        *entry += 1;
-                    }
-                }
-            }
-        });
-
-    lines_map.values().filter(|x| **x >= 2).count()
+        .filter(|s| is_horizontal_or_vertical(s))
+        .for_each(|s| cover(s, &mut lines_map));
+    overlaps(&lines_map)
 }
 
 #[aoc(day5, part2)]
-fn part2(input: &[LineSegment]) -> usize {
-    let mut lines_map: HashMap<(i32, i32), usize> = HashMap::default();
-    input.iter().for_each(|s| {
-        if s.is_horizontal_or_vertical() {
-            let min_x = s.p1.0.min(s.p2.0);
-            let max_x = s.p1.0.max(s.p2.0);
-            let min_y = s.p1.1.min(s.p2.1);
-            let max_y = s.p1.1.max(s.p2.1);
-            for x in min_x..=max_x {
-                for y in min_y..=max_y {
-                    let entry = lines_map.entry((x, y)).or_insert(0);
-                    *entry += 1;
-                }
-            }
-        } else if s.is_diagonal() {
-            let minx_point = if s.p1.0 < s.p2.0 { s.p1 } else { s.p2 };
-            let maxx_point = if s.p1.0 < s.p2.0 { s.p2 } else { s.p1 };
-            let delta_y = if maxx_point.1 - minx_point.1 > 0 {
-                1
-            } else {
-                -1
-            };
-            let mut y = minx_point.1;
-            for x in minx_point.0..=maxx_point.0 {
-                let entry = lines_map.entry((x, y)).or_insert(0);
-                *entry += 1;
-                y += delta_y;
-            }
+pub fn part2(input: &[Segment<i32>]) -> usize {
+    let mut lines_map: HashMap<Point2<i32>, usize> = HashMap::default();
+    input.iter().for_each(|s| cover(s, &mut lines_map));
+    overlaps(&lines_map)
+}
+
+/// Both parts in a single pass, memory growing with the covered area instead of the number of
+/// segments
+pub fn stream(input: impl BufRead) -> Result<(usize, usize), AocError> {
+    let mut straight_map: HashMap<Point2<i32>, usize> = HashMap::default();
+    let mut lines_map: HashMap<Point2<i32>, usize> = HashMap::default();
+    for_each_line(5, input, |line| {
+        let segment = parse_segment(line)?;
+        if is_horizontal_or_vertical(&segment) {
+            cover(&segment, &mut straight_map);
         }
-    });
+        cover(&segment, &mut lines_map);
+        Ok(())
+    })?;
+    Ok((overlaps(&straight_map), overlaps(&lines_map)))
+}
 
-    lines_map.values().filter(|x| **x >= 2).count()
+const EXAMPLE: &str = include_str!("../examples/day5/example.txt");
+
+pub fn solver() -> impl Solver {
+    Solution {
+        day: 5,
+        title: "Hydrothermal Venture",
+        examples: &[Example {
+            input: EXAMPLE,
+            part1: Some("5"),
+            part2: Some("12"),
+        }],
+        parse,
+        valid: |segments| valid(segments),
+        part1: |x| part1(x),
+        part2: |x| part2(x),
+        variants: vec![Variant {
+            name: oracle::VARIANT,
+            part1: Some(|x| oracle::day5::part1(x)),
+            part2: Some(|x| oracle::day5::part2(x)),
+        }],
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
 
-    const INPUT: &'static str = "0,9 -> 5,9
-8,0 -> 0,8
-9,4 -> 3,4
-2,2 -> 2,1
-7,0 -> 7,4
-6,4 -> 2,0
-0,9 -> 2,9
-3,4 -> 1,4
-0,0 -> 8,8
-5,5 -> 8,2";
-
     #[test]
-    fn should_work() -> Result<(), ParseIntError> {
-        let input = parse(INPUT)?;
+    fn should_work() -> Result<(), AocError> {
+        let input = parse(EXAMPLE)?;
         assert_eq!(part1(&input), 5);
         assert_eq!(part2(&input), 12);
         Ok(())
     }
+
+    #[test]
+    fn should_bound_coordinates() {
+        let err = parse("0,9 -> 5,9\n-2147483648,0 -> 2147483647,0").err();
+        let kind = || ParseErrorKind::Expected("a coordinate from 0 to 9999");
+        assert_eq!(err, Some(AocError::parse(5, 2, 1, kind())));
+        let err = parse("0,9 -> 5,10000").err();
+        assert_eq!(err, Some(AocError::parse(5, 1, 10, kind())));
+    }
+
+    #[test]
+    fn should_report_malformed_segments() {
+        assert_eq!(
+            parse("0,9 -> 5,9\n8,0 -> 08").err(),
+            Some(AocError::parse(5, 2, 10, ParseErrorKind::Expected("`,`")))
+        );
+        assert!(matches!(
+            parse("0,9 -> 5,x9").err(),
+            Some(AocError::Parse {
+                line: 1,
+                column: 10,
+                ..
+            })
+        ));
+    }
 }
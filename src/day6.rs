@@ -1,43 +1,112 @@
-use std::num::ParseIntError;
+use crate::arith;
+use crate::error::{column_of, parse_field, AocError, ParseErrorKind};
+use crate::input::normalize;
+#[cfg(feature = "std")]
+use crate::oracle;
+#[cfg(feature = "std")]
+use crate::solver::{Example, Solution, Solver, Variant};
+use alloc::vec::Vec;
 
-#[aoc_generator(day6)]
-fn parse(input: &str) -> Result<Vec<usize>, ParseIntError> {
-    input.split(",").map(|x| x.parse()).collect()
+/// Parses the comma-separated timers of the lanternfish
+#[cfg_attr(feature = "std", aoc_generator(day6))]
+pub fn parse(input: &str) -> Result<Vec<usize>, AocError> {
+    let input = &normalize(input);
+    input.split(',').map(|x| parse_timer(input, x)).collect()
 }
 
-#[aoc(day6, part1)]
-fn part1(input: &[usize]) -> usize {
+/// Parses `field`, a subslice of `line`
+fn parse_timer(line: &str, field: &str) -> Result<usize, AocError> {
+    let timer = parse_field(6, line, field)?;
+    // Timers index the buckets in `fishes_for_day`
+    if timer > 8 {
+        let kind = ParseErrorKind::Expected("a timer between 0 and 8");
+        return Err(AocError::parse(6, 1, column_of(line, field), kind));
+    }
+    Ok(timer)
+}
+
+/// Every field of the line that isn't a timer, and the lines after it
+pub fn lint(input: &str) -> Vec<AocError> {
+    let mut lines = input.lines();
+    let first = lines.next().unwrap_or("");
+    let mut errors: Vec<AocError> = first
+        .split(',')
+        .filter_map(|x| parse_timer(first, x).err())
+        .collect();
+    if lines.next().is_some() {
+        let kind = ParseErrorKind::Expected("a single line of timers");
+        errors.push(AocError::parse(6, 2, 1, kind));
+    }
+    errors
+}
+
+#[cfg_attr(feature = "std", aoc(day6, part1))]
+pub fn part1(input: &[usize]) -> Result<usize, AocError> {
     fishes_for_day(input, 80)
 }
 
-#[aoc(day6, part2)]
-fn part2(input: &[usize]) -> usize {
+#[cfg_attr(feature = "std", aoc(day6, part2))]
+pub fn part2(input: &[usize]) -> Result<usize, AocError> {
     fishes_for_day(input, 256)
 }
 
-fn fishes_for_day(input: &[usize], days: usize) -> usize {
+/// Number of lanternfish after `days`, fish being counted per timer value rather than one by one.
+/// Panics if a timer is above 8.
+pub fn fishes_for_day(input: &[usize], days: usize) -> Result<usize, AocError> {
     let mut fishes: [usize; 9] = [0, 0, 0, 0, 0, 0, 0, 0, 0];
     input.iter().for_each(|x| fishes[*x] += 1);
-    (0..days).for_each(|_| {
+    for _ in 0..days {
         let newborns = fishes[0];
         (0..=7).for_each(|i| fishes[i] = fishes[i + 1]);
-        fishes[6] += newborns;
+        fishes[6] = arith::add(6, fishes[6], newborns)?;
         fishes[8] = newborns;
-    });
-    fishes.into_iter().sum()
+    }
+    arith::sum(6, fishes)
+}
+
+#[cfg(any(test, feature = "std"))]
+const EXAMPLE: &str = include_str!("../examples/day6/example.txt");
+
+#[cfg(feature = "std")]
+pub fn solver() -> impl Solver {
+    Solution {
+        day: 6,
+        title: "Lanternfish",
+        examples: &[Example {
+            input: EXAMPLE,
+            part1: Some("5934"),
+            part2: Some("26984457539"),
+        }],
+        parse,
+        valid: |timers| timers.iter().all(|&t| t <= 8),
+        part1: |x| part1(x),
+        part2: |x| part2(x),
+        variants: vec![Variant {
+            name: oracle::VARIANT,
+            part1: Some(|x| Ok(oracle::day6::part1(x))),
+            part2: None,
+        }],
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
 
-    const INPUT: &'static str = "3,4,3,1,2";
+    #[test]
+    fn should_work() -> Result<(), AocError> {
+        let data = parse(EXAMPLE)?;
+        assert_eq!(part1(&data)?, 5934);
+        assert_eq!(part2(&data)?, 26984457539);
+        Ok(())
+    }
 
+    #[cfg(feature = "checked-arith")]
     #[test]
-    fn should_work() -> Result<(), ParseIntError> {
-        let data = parse(INPUT)?;
-        assert_eq!(part1(&data), 5934);
-        assert_eq!(part2(&data), 26984457539);
+    fn should_report_overflows() -> Result<(), AocError> {
+        let data = parse(EXAMPLE)?;
+        assert_eq!(fishes_for_day(&data, 400)?, 7_563_375_241_248_839);
+        assert_eq!(fishes_for_day(&data, 600), Err(AocError::Overflow(6)));
         Ok(())
     }
 }
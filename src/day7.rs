@@ -1,56 +1,189 @@
-use std::num::ParseIntError;
+use crate::arith;
+use crate::error::{parse_field, AocError, ParseErrorKind};
+use crate::input::normalize;
+use crate::oracle;
+use crate::solver::{Example, Solution, Solver, Variant};
 
+/// Parses the comma-separated horizontal positions of the crabs
 #[aoc_generator(day7)]
-fn parse(input: &str) -> Result<Vec<i32>, ParseIntError> {
-    input.split(",").map(|x| x.parse()).collect()
+pub fn parse(input: &str) -> Result<Vec<i32>, AocError> {
+    let input = &normalize(input);
+    input.split(',').map(|x| parse_field(7, input, x)).collect()
 }
 
-#[aoc(day7, part1)]
-fn part1(input: &[i32]) -> i32 {
-    let mut prev = i32::MAX;
-    for target in 0.. {
-        let new_fuel = input.iter().map(|x| (target - x).abs()).sum();
-        if new_fuel > prev {
-            return prev;
-        }
-        prev = new_fuel
+/// Every field of the line that isn't a position, and the lines after it
+pub fn lint(input: &str) -> Vec<AocError> {
+    let mut lines = input.lines();
+    let first = lines.next().unwrap_or("");
+    let mut errors: Vec<AocError> = first
+        .split(',')
+        .filter_map(|x| parse_field::<i32>(7, first, x).err())
+        .collect();
+    if lines.next().is_some() {
+        let kind = ParseErrorKind::Expected("a single line of positions");
+        errors.push(AocError::parse(7, 2, 1, kind));
     }
+    errors
+}
 
-    unreachable!()
+#[aoc(day7, part1)]
+pub fn part1(input: &[i32]) -> Result<i32, AocError> {
+    cheapest_alignment(input, |distance| distance)
 }
 
 #[aoc(day7, part2)]
-fn part2(input: &[i32]) -> i32 {
-    let mut prev = i32::MAX;
-    for target in 0.. {
-        let new_fuel = input
-            .iter()
-            .map(|x| {
-                let n = (target - x).abs();
-                (n * (n + 1)) / 2
-            })
-            .sum();
-        if new_fuel > prev {
-            return prev;
+pub fn part2(input: &[i32]) -> Result<i32, AocError> {
+    cheapest_alignment(input, triangular)
+}
+
+/// Cost of moving a crab by `distance` when each step costs one more than the previous one
+fn triangular(distance: i128) -> i128 {
+    distance * (distance + 1) / 2
+}
+
+/// Fuel spent to align every crab on `target`. It's counted in `i128`, which holds the cost of
+/// any distance between two `i32` for more crabs than fit in memory, so that only the answer can
+/// be too large.
+fn fuel(input: &[i32], target: i64, cost: impl Fn(i128) -> i128) -> i128 {
+    input
+        .iter()
+        .map(|&x| cost(i128::from(target.abs_diff(i64::from(x)))))
+        .sum()
+}
+
+fn answer(fuel: i128) -> Result<i32, AocError> {
+    arith::to_i32(7, fuel)
+}
+
+/// Least fuel spent to align every crab on a position, moving a crab by `distance` costing
+/// `cost(distance)`. The total fuel must decrease then increase as the position goes right from
+/// the leftmost crab, so the position from which it stops decreasing is searched by bisection.
+pub fn cheapest_alignment(input: &[i32], cost: impl Fn(i128) -> i128) -> Result<i32, AocError> {
+    let (Some(&leftmost), Some(&rightmost)) = (input.iter().min(), input.iter().max()) else {
+        return Ok(0);
+    };
+    let (mut low, mut high) = (i64::from(leftmost), i64::from(rightmost));
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if fuel(input, middle + 1, &cost) >= fuel(input, middle, &cost) {
+            high = middle;
+        } else {
+            low = middle + 1;
         }
-        prev = new_fuel
     }
+    answer(fuel(input, low, cost))
+}
+
+/// Fuel spent to align every crab on the median position, where moving one step further would
+/// bring as many crabs closer as it takes away
+pub fn median_alignment(input: &[i32]) -> Result<i32, AocError> {
+    let mut sorted = input.to_vec();
+    sorted.sort_unstable();
+    let median = sorted.get(sorted.len() / 2).copied().unwrap_or(0);
+    answer(fuel(input, i64::from(median), |distance| distance))
+}
+
+/// Fuel spent when moving a crab costs more at each step: the cheapest position is within half
+/// a step of the mean, so only the positions around it are tried
+pub fn mean_alignment(input: &[i32]) -> Result<i32, AocError> {
+    if input.is_empty() {
+        return Ok(0);
+    }
+    let sum: i64 = input.iter().map(|&x| i64::from(x)).sum();
+    let mean = sum.div_euclid(input.len() as i64);
+    let cheapest = (mean - 1..=mean + 1)
+        .map(|target| fuel(input, target, triangular))
+        .min()
+        .unwrap(); // safe because the range isn't empty
+    answer(cheapest)
+}
 
-    unreachable!()
+const EXAMPLE: &str = include_str!("../examples/day7/example.txt");
+
+pub fn solver() -> impl Solver {
+    Solution {
+        day: 7,
+        title: "The Treachery of Whales",
+        examples: &[Example {
+            input: EXAMPLE,
+            part1: Some("37"),
+            part2: Some("168"),
+        }],
+        parse,
+        valid: |_| true,
+        part1: |x| part1(x),
+        part2: |x| part2(x),
+        variants: vec![
+            Variant {
+                name: "median",
+                part1: Some(|x| median_alignment(x)),
+                part2: None,
+            },
+            Variant {
+                name: "mean",
+                part1: None,
+                part2: Some(|x| mean_alignment(x)),
+            },
+            Variant {
+                name: oracle::VARIANT,
+                part1: Some(|x| Ok(oracle::day7::part1(x))),
+                part2: Some(|x| Ok(oracle::day7::part2(x))),
+            },
+        ],
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
-    use std::num::ParseIntError;
 
-    const INPUT: &'static str = "16,1,2,0,4,2,7,1,2,14";
+    #[test]
+    fn should_work() -> Result<(), AocError> {
+        let data = parse(EXAMPLE)?;
+        assert_eq!(part1(&data)?, 37);
+        assert_eq!(part2(&data)?, 168);
+        assert_eq!(median_alignment(&data)?, 37);
+        assert_eq!(mean_alignment(&data)?, 168);
+        Ok(())
+    }
+
+    #[test]
+    fn should_handle_negative_positions() -> Result<(), AocError> {
+        let data = parse("-16,-1,-2,0,-4,-2,-7,-1,-2,-14")?;
+        assert_eq!(part1(&data)?, 37);
+        assert_eq!(part2(&data)?, 168);
+        Ok(())
+    }
+
+    #[test]
+    fn should_handle_large_distances() -> Result<(), AocError> {
+        // 50000 steps cost more fuel than an `i32` holds, though the cheapest alignment doesn't
+        let data = parse("0,50000")?;
+        assert_eq!(part1(&data)?, 50000);
+        assert_eq!(part2(&data)?, 625025000);
+        assert_eq!(mean_alignment(&data)?, 625025000);
+
+        let data = parse("2147483647")?;
+        assert_eq!((part1(&data)?, part2(&data)?), (0, 0));
+        assert_eq!((median_alignment(&data)?, mean_alignment(&data)?), (0, 0));
+        // Whatever the answers, with or without `checked-arith`, they don't panic
+        let data = parse("-2147483648,2147483647")?;
+        let _ = (part1(&data), part2(&data));
+        let _ = (median_alignment(&data), mean_alignment(&data));
+        Ok(())
+    }
 
+    #[cfg(feature = "checked-arith")]
     #[test]
-    fn should_work() -> Result<(), ParseIntError> {
-        let data = parse(INPUT)?;
-        assert_eq!(part1(&data), 37);
-        assert_eq!(part2(&data), 168);
+    fn should_report_overflows() -> Result<(), AocError> {
+        let data = parse("-2147483648,2147483647")?;
+        assert_eq!(part1(&data), Err(AocError::Overflow(7)));
+        assert_eq!(part2(&data), Err(AocError::Overflow(7)));
+        let data = parse("0,100000")?;
+        assert_eq!(part2(&data), Err(AocError::Overflow(7)));
+        assert_eq!(mean_alignment(&data), Err(AocError::Overflow(7)));
+        let data = parse("-2000000000,0,2000000000")?;
+        assert_eq!(median_alignment(&data), Err(AocError::Overflow(7)));
         Ok(())
     }
 }
@@ -1,8 +1,19 @@
+use crate::error::{column_of, lint_lines, split_pair, AocError, ParseErrorKind};
+use crate::input::normalize;
+use crate::oracle;
+use crate::solver::{Example, Solution, Solver, Variant};
+use crate::stream::for_each_line;
 use std::collections::HashMap;
+use std::io::BufRead;
+use std::str::FromStr;
 
-struct Note {
-    inputs: Vec<String>,
-    outputs: Vec<String>,
+/// An entry of the notes: the ten unique signal patterns, then the four digits of the display,
+/// made of segments `a` to `g` in any order
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Note {
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
 }
 
 #[derive(Debug)]
@@ -25,92 +36,197 @@ impl Note {
                 .collect(),
         )
     }
+
+    /// Number of output digits with a unique number of segments (1, 4, 7 and 8)
+    pub fn easy_digits(&self) -> usize {
+        self.outputs
+            .iter()
+            .filter(|i| matches!(i.len(), 2 | 3 | 4 | 7))
+            .count()
+    }
+
+    /// Value shown by the four output digits, once the wires are untangled
+    pub fn output_value(&self) -> Result<u32, AocError> {
+        let mappings = self.compute_mappings();
+        self.outputs.iter().try_fold(0, |value, o| {
+            let digit = mappings
+                .get_digit(o)
+                .ok_or_else(|| AocError::no_solution(8, "an output matches no digit"))?;
+            Ok(value * 10 + digit)
+        })
+    }
 }
 
 impl Mappings {
-    pub fn get_digit(&self, input: &str) -> char {
-        let val: usize = input.chars().map(|x| self.0[&x]).sum();
+    fn get_digit(&self, input: &str) -> Option<u32> {
+        let val: usize = input
+            .chars()
+            .map(|x| self.0.get(&x))
+            .sum::<Option<usize>>()?;
         match val {
-            42 => '0',
-            17 => '1',
-            34 => '2',
-            39 => '3',
-            30 => '4',
-            37 => '5',
-            41 => '6',
-            25 => '7',
-            49 => '8',
-            45 => '9',
-            _ => panic!("Unknown digit"),
+            42 => Some(0),
+            17 => Some(1),
+            34 => Some(2),
+            39 => Some(3),
+            30 => Some(4),
+            37 => Some(5),
+            41 => Some(6),
+            25 => Some(7),
+            49 => Some(8),
+            45 => Some(9),
+            _ => None,
         }
     }
 }
 
+/// Splits `part` (a subslice of `line`) into `count` patterns of segments `a` to `g`.
+fn parse_patterns(
+    line: &str,
+    part: &str,
+    count: usize,
+    expected: &'static str,
+) -> Result<Vec<String>, AocError> {
+    let patterns: Vec<String> = part
+        .split(' ')
+        .map(|p| {
+            match p.find(|c: char| !('a'..='g').contains(&c)) {
+                Some(idx) => {
+                    let found = p[idx..].chars().next().unwrap(); // safe because idx < p.len()
+                    let column = column_of(line, p) + idx;
+                    Err(AocError::parse(
+                        8,
+                        1,
+                        column,
+                        ParseErrorKind::UnexpectedChar(found),
+                    ))
+                }
+                None if p.is_empty() => {
+                    let kind = ParseErrorKind::Expected("a segment pattern");
+                    Err(AocError::parse(8, 1, column_of(line, p), kind))
+                }
+                None => Ok(p.to_string()),
+            }
+        })
+        .collect::<Result<_, _>>()?;
+    if patterns.len() != count {
+        let column = column_of(line, part);
+        return Err(AocError::parse(
+            8,
+            1,
+            column,
+            ParseErrorKind::Expected(expected),
+        ));
+    }
+    Ok(patterns)
+}
+
+/// Whether `notes` could come from `parse`: 10 patterns then 4 digits of segments `a` to `g`
+fn valid(notes: &[Note]) -> bool {
+    let pattern = |p: &String| !p.is_empty() && p.chars().all(|c| ('a'..='g').contains(&c));
+    notes.iter().all(|n| {
+        n.inputs.len() == 10
+            && n.outputs.len() == 4
+            && n.inputs.iter().chain(&n.outputs).all(pattern)
+    })
+}
+
+impl FromStr for Note {
+    type Err = AocError;
+
+    fn from_str(l: &str) -> Result<Self, Self::Err> {
+        let (inputs, outputs) = split_pair(8, l, " | ", "` | `")?;
+        let inputs = parse_patterns(l, inputs, 10, "10 signal patterns")?;
+        let outputs = parse_patterns(l, outputs, 4, "4 output digits")?;
+        Ok(Note { inputs, outputs })
+    }
+}
+
+/// Parses one `<patterns> | <outputs>` note per line
 #[aoc_generator(day8)]
-fn parse(input: &str) -> Vec<Note> {
+pub fn parse(input: &str) -> Result<Vec<Note>, AocError> {
+    let input = &normalize(input);
     input
         .lines()
-        .map(|l| {
-            let parts: Vec<&str> = l.split(" | ").collect();
-            let inputs = parts[0].split(" ").map(|x| x.to_string()).collect();
-            let outputs = parts[1].split(" ").map(|x| x.to_string()).collect();
-            Note { inputs, outputs }
-        })
+        .enumerate()
+        .map(|(idx, l)| l.parse().map_err(|e: AocError| e.at_line(idx + 1)))
         .collect()
 }
 
+/// Every line that isn't a note
+pub fn lint(input: &str) -> Vec<AocError> {
+    lint_lines(input, Note::from_str)
+}
+
 #[aoc(day8, part1)]
-fn part1(input: &[Note]) -> usize {
-    input
-        .iter()
-        .map(|i| {
-            i.outputs
-                .iter()
-                .filter(|i| match i.len() {
-                    2 | 3 | 4 | 7 => true,
-                    _ => false,
-                })
-                .count()
-        })
-        .sum()
+pub fn part1(input: &[Note]) -> usize {
+    input.iter().map(Note::easy_digits).sum()
 }
 
 #[aoc(day8, part2)]
-fn part2(input: &[Note]) -> u32 {
-    input
-        .iter()
-        .map(|note| {
-            let mappings = note.compute_mappings();
-            note.outputs
-                .iter()
-                .map(|o| mappings.get_digit(o))
-                .collect::<String>()
-                .parse::<u32>()
-                .unwrap()
-        })
-        .sum()
+pub fn part2(input: &[Note]) -> Result<u32, AocError> {
+    input.iter().map(Note::output_value).sum()
+}
+
+/// Both parts in a single pass, notes being independent from each other.
+/// The sum of part 2 is a `u64`, as long inputs quickly overflow a `u32`.
+pub fn stream(input: impl BufRead) -> Result<(usize, Result<u64, AocError>), AocError> {
+    let mut easy_digits = 0;
+    let mut total = Ok(0);
+    for_each_line(8, input, |line| {
+        let note: Note = line.parse()?;
+        easy_digits += note.easy_digits();
+        if let Ok(sum) = total {
+            total = note.output_value().map(|value| sum + value as u64);
+        }
+        Ok(())
+    })?;
+    Ok((easy_digits, total))
+}
+
+const EXAMPLE: &str = include_str!("../examples/day8/example.txt");
+
+pub fn solver() -> impl Solver {
+    Solution {
+        day: 8,
+        title: "Seven Segment Search",
+        examples: &[Example {
+            input: EXAMPLE,
+            part1: Some("26"),
+            part2: Some("61229"),
+        }],
+        parse,
+        valid: |notes| valid(notes),
+        part1: |x| part1(x),
+        part2: |x| part2(x),
+        variants: vec![Variant {
+            name: oracle::VARIANT,
+            part1: Some(|x| oracle::day8::part1(x)),
+            part2: Some(|x| oracle::day8::part2(x)),
+        }],
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
 
-    const INPUT: &'static str =
-        "be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
-edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc
-fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg
-fbegcd cbd adcefb dageb afcb bc aefdc ecdab fgdeca fcdbega | efabcd cedba gadfec cb
-aecbfdg fbg gf bafeg dbefa fcge gcbea fcaegb dgceab fcbdga | gecf egdcabf bgf bfgea
-fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb
-dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf | cefg dcbef fcge gbcadfe
-bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef
-egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
-gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce";
-
     #[test]
-    fn should_work() {
-        let data = parse(INPUT);
+    fn should_work() -> Result<(), AocError> {
+        let data = parse(EXAMPLE)?;
         assert_eq!(part1(&data), 26);
-        assert_eq!(part2(&data), 61229);
+        assert_eq!(part2(&data)?, 61229);
+        Ok(())
+    }
+
+    #[test]
+    fn should_report_unknown_segment() {
+        let note = Note {
+            inputs: Vec::new(),
+            outputs: vec!["z".to_string()],
+        };
+        assert!(matches!(
+            part2(&[note]),
+            Err(AocError::NoSolution { day: 8, .. })
+        ));
     }
 }
@@ -1,118 +1,177 @@
-use std::collections::HashSet;
+use crate::error::AocError;
+use crate::grid::{Grid, Position};
+use crate::input::normalize;
+use crate::oracle;
+use crate::solver::{Example, Solution, Solver, Variant};
+use crate::viz::{Frame, Rgb, Visualize};
 
-struct Heightmap {
-    heights: Vec<Vec<i32>>,
-    width: i32,
-    height: i32,
-}
+/// Heights from 0 to 9 of the cave floor
+pub type Heightmap = Grid<u8>;
 
-struct Point {
-    x: i32,
-    y: i32,
-    height: i32,
+/// Parses one row of digits per line
+#[aoc_generator(day9)]
+pub fn parse(input: &str) -> Result<Heightmap, AocError> {
+    let input = &normalize(input);
+    Grid::parse_digits(input).map_err(|e| AocError::from_grid(9, e))
 }
 
-impl Heightmap {
-    pub fn get(&self, x: i32, y: i32) -> Option<i32> {
-        self.heights
-            .get(y as usize)
-            .and_then(|l| l.get(x as usize))
-            .cloned()
-    }
+/// Every row with other cells than digits or a different width than the first one
+pub fn lint(input: &str) -> Vec<AocError> {
+    let errors = Grid::lint_digits(input).into_iter();
+    errors.map(|e| AocError::from_grid(9, e)).collect()
+}
 
-    pub fn neighbors(&self, x: i32, y: i32) -> impl Iterator<Item = Point> + '_ {
-        [(x - 1, y), (x, y - 1), (x + 1, y), (x, y + 1)]
-            .into_iter()
-            .filter_map(|(x, y)| self.get(x, y).map(|height| Point { x, y, height }))
-    }
+/// Whether every orthogonal neighbor of `pos` is higher
+pub fn is_low_point(input: &Heightmap, pos: Position) -> bool {
+    let height = input[pos];
+    input.orthogonal_neighbors(pos).all(|p| input[p] > height)
 }
 
-#[aoc_generator(day9)]
-fn parse(input: &str) -> Heightmap {
-    let heights: Vec<Vec<i32>> = input
-        .lines()
-        .map(|l| l.chars().map(|x| x.to_digit(10).unwrap() as i32).collect())
-        .collect();
-    let width = heights[0].len() as i32;
-    let height = heights.len() as i32;
-
-    Heightmap {
-        heights,
-        width,
-        height,
+/// Size of every basin: areas of heights below 9, which are walls
+pub fn basin_sizes(input: &Heightmap) -> Vec<usize> {
+    let mut explored: Grid<bool> = input.map(|_| false);
+    let mut basin_sizes: Vec<usize> = Vec::new();
+    for pos in input.positions() {
+        if explored[pos] || input[pos] == 9 {
+            continue;
+        }
+        basin_sizes.push(basin_size(input, &mut explored, pos));
     }
+    basin_sizes
 }
 
 #[aoc(day9, part1)]
-fn part1(input: &Heightmap) -> i32 {
-    (0..input.width)
-        .flat_map(|x| (0..input.height).map(move |y| (x, y)))
-        .filter_map(|(x, y)| {
-            let height = input.get(x, y).expect("Failed to get a valid height");
-            if input.neighbors(x, y).all(|p| p.height > height) {
-                Some(height + 1)
-            } else {
-                None
-            }
-        })
+pub fn part1(input: &Heightmap) -> u32 {
+    input
+        .positions()
+        .filter(|&pos| is_low_point(input, pos))
+        .map(|pos| input[pos] as u32 + 1)
         .sum()
 }
 
 #[aoc(day9, part2)]
-fn part2(input: &Heightmap) -> usize {
-    // Compute and store all basin sizes in a vec
-    let mut explored: HashSet<(i32, i32)> = HashSet::default();
-    let mut basin_sizes: Vec<usize> = Vec::new();
-    for x in 0..input.width {
-        for y in 0..input.height {
-            if explored.contains(&(x, y)) {
-                continue;
-            }
-            let basin = basin_size(input, &mut explored, (x, y));
-            basin_sizes.push(basin - 1); // -1 because starting point is not considered a part of a basin
-        }
-    }
-
-    // Then retrieve the top 3 sizes and multiply them
+pub fn part2(input: &Heightmap) -> usize {
+    // Retrieve the top 3 sizes and multiply them
     // This is not the greatest in terms of performance, but is at least readable
+    let mut basin_sizes = basin_sizes(input);
     basin_sizes.sort();
     basin_sizes.iter().rev().take(3).cloned().product()
 }
 
-/// Gets the size of the basin that the position at (x,y) is a part of.
-/// Conveniently adds (x,y) to the "explored" set
-fn basin_size(input: &Heightmap, explored: &mut HashSet<(i32, i32)>, (x, y): (i32, i32)) -> usize {
-    // doing this first avoids endless recursion of a point A adding its neighbor B that in turns
-    // adds point A and so on
-    explored.insert((x, y));
-    input
-        .neighbors(x, y)
-        .filter_map(|p| {
-            let pos = (p.x, p.y);
-            if p.height != 9 && !explored.contains(&pos) {
-                Some(basin_size(input, explored, pos))
-            } else {
-                None
+/// Gets the size of the basin that the position at (x,y) is a part of, marking all of it as
+/// "explored". Filled with an explicit stack, as basins can be far too large to recurse over.
+fn basin_size(input: &Heightmap, explored: &mut Grid<bool>, pos: Position) -> usize {
+    // Marking positions when they're pushed rather than popped keeps them from being pushed twice
+    explored[pos] = true;
+    let mut open = vec![pos];
+    let mut size = 0;
+    while let Some(pos) = open.pop() {
+        size += 1;
+        for p in input.orthogonal_neighbors(pos) {
+            if input[p] != 9 && !explored[p] {
+                explored[p] = true;
+                open.push(p);
             }
-        })
-        .sum::<usize>()
-        + 1
+        }
+    }
+    size
+}
+
+/// Basins being filled one after the other, the last one in white.
+/// Walls (9s) are black and heights that are yet to be explored are shades of gray.
+pub struct Basins<'a>(pub &'a Heightmap);
+
+const BASIN_COLORS: [Rgb; 6] = [
+    [230, 80, 80],
+    [80, 200, 80],
+    [80, 120, 230],
+    [220, 200, 60],
+    [200, 90, 220],
+    [60, 200, 210],
+];
+
+impl Visualize for Basins<'_> {
+    fn frames(&self) -> Vec<Frame> {
+        let input = self.0;
+        let mut explored: Grid<bool> = input.map(|_| false);
+        let mut basins: Grid<Option<usize>> = input.map(|_| None);
+        let frame = |basins: &Grid<Option<usize>>, last: usize| {
+            Frame::Color(Grid::from_fn(input.width(), input.height(), |pos| {
+                match (input[pos], basins[pos]) {
+                    (9, _) => [0, 0, 0],
+                    (_, Some(basin)) if basin == last => [255, 255, 255],
+                    (_, Some(basin)) => BASIN_COLORS[basin % BASIN_COLORS.len()],
+                    (height, None) => [height * 15 + 40; 3],
+                }
+            }))
+        };
+        let mut frames = vec![frame(&basins, usize::MAX)];
+        for pos in input.positions() {
+            if explored[pos] || input[pos] == 9 {
+                continue;
+            }
+            basin_size(input, &mut explored, pos);
+            let basin = frames.len() - 1;
+            for p in input.positions() {
+                if explored[p] && input[p] != 9 && basins[p].is_none() {
+                    basins[p] = Some(basin);
+                }
+            }
+            frames.push(frame(&basins, basin));
+        }
+        frames
+    }
+}
+
+const EXAMPLE: &str = include_str!("../examples/day9/example.txt");
+
+pub fn solver() -> impl Solver {
+    Solution {
+        day: 9,
+        title: "Smoke Basin",
+        examples: &[Example {
+            input: EXAMPLE,
+            part1: Some("15"),
+            part2: Some("1134"),
+        }],
+        parse,
+        valid: |heights| !heights.is_empty() && heights.iter().all(|&h| h <= 9),
+        part1,
+        part2,
+        variants: vec![Variant {
+            name: oracle::VARIANT,
+            part1: Some(oracle::day9::part1),
+            part2: Some(oracle::day9::part2),
+        }],
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
 
-    const INPUT: &'static str = "2199943210
-3987894921
-9856789892
-8767896789
-9899965678";
-
     #[test]
-    fn should_work() {
-        let data = parse(INPUT);
+    fn should_work() -> Result<(), AocError> {
+        let data = parse(EXAMPLE)?;
         assert_eq!(part1(&data), 15);
         assert_eq!(part2(&data), 1134);
+        Ok(())
+    }
+
+    #[test]
+    fn should_size_basins() -> Result<(), AocError> {
+        assert_eq!(basin_sizes(&parse(EXAMPLE)?), vec![3, 9, 14, 9]);
+        // Basins whose first cell isn't next to a wall, and basins touching the same wall
+        assert_eq!(basin_sizes(&parse("19\n91")?), vec![1, 1]);
+        assert_eq!(basin_sizes(&parse("191\n999\n111")?), vec![1, 1, 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn should_fill_large_basins() {
+        // A single basin too large to be filled recursively
+        let input = Grid::from_vec(700, vec![1; 700 * 700]);
+        assert_eq!(basin_sizes(&input), vec![700 * 700]);
+        assert_eq!(Basins(&input).frames().len(), 2);
     }
 }
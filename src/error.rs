@@ -0,0 +1,254 @@
+//! Crate-wide error type returned by every generator and by the runner.
+//! Line and column numbers are 1-based, so they can be looked up directly in an editor.
+
+#[cfg(feature = "std")]
+use crate::grid::GridParseError;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::num::ParseIntError;
+use core::ops::RangeInclusive;
+use core::str::FromStr;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::io;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AocError {
+    #[error("Day {day}, line {line}, column {column}: {kind}")]
+    Parse {
+        day: u8,
+        line: usize,
+        column: usize,
+        kind: ParseErrorKind,
+    },
+    #[error("Day {0} is not solved")]
+    UnknownDay(u8),
+    #[error("Part {0} does not exist")]
+    UnknownPart(u8),
+    #[error("Day {day} part {part} has no {variant:?} variant")]
+    UnknownVariant { day: u8, part: u8, variant: String },
+    #[error("Day {day}: no solution, {reason}")]
+    NoSolution { day: u8, reason: &'static str },
+    #[error("Day {day} timed out after {limit:?}")]
+    TimedOut { day: u8, limit: Duration },
+    #[error("Day {0} panicked")]
+    Panicked(u8),
+    #[error("Day {0}: arithmetic overflow")]
+    Overflow(u8),
+    #[cfg(feature = "std")]
+    #[error("Day {day}, line {line}: failed to read input, {kind}")]
+    Io {
+        day: u8,
+        line: usize,
+        kind: io::ErrorKind,
+    },
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    #[error("Failed to parse int: {0}")]
+    Int(#[from] ParseIntError),
+    #[error("Unexpected character {0:?}")]
+    UnexpectedChar(char),
+    #[error("Unknown token {0:?}")]
+    UnknownToken(String),
+    #[error("Expected {0}")]
+    Expected(&'static str),
+    #[error("Input is empty")]
+    Empty,
+    #[error("Row has {found} cells, expected {expected}")]
+    RaggedRow { expected: usize, found: usize },
+}
+
+impl AocError {
+    pub fn parse(day: u8, line: usize, column: usize, kind: impl Into<ParseErrorKind>) -> Self {
+        AocError::Parse {
+            day,
+            line,
+            column,
+            kind: kind.into(),
+        }
+    }
+
+    /// Moves an error reported relative to a single line (or block) so that its first line
+    /// is `line` of the whole input. Used by generators around per-line `FromStr` impls.
+    pub fn at_line(self, line: usize) -> Self {
+        match self {
+            AocError::Parse {
+                day,
+                line: relative,
+                column,
+                kind,
+            } => AocError::Parse {
+                day,
+                line: relative + line - 1,
+                column,
+                kind,
+            },
+            other => other,
+        }
+    }
+
+    /// Same as `at_line` for errors reported relative to a field starting at `column`.
+    pub fn at_column(self, column: usize) -> Self {
+        match self {
+            AocError::Parse {
+                day,
+                line,
+                column: relative,
+                kind,
+            } => AocError::Parse {
+                day,
+                line,
+                column: relative + column - 1,
+                kind,
+            },
+            other => other,
+        }
+    }
+
+    pub fn no_solution(day: u8, reason: &'static str) -> Self {
+        AocError::NoSolution { day, reason }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn from_grid(day: u8, error: GridParseError) -> Self {
+        match error {
+            GridParseError::Empty => AocError::parse(day, 1, 1, ParseErrorKind::Empty),
+            GridParseError::RaggedRow {
+                line,
+                expected,
+                found,
+            } => AocError::parse(
+                day,
+                line,
+                expected.min(found) + 1,
+                ParseErrorKind::RaggedRow { expected, found },
+            ),
+            GridParseError::InvalidCell {
+                line,
+                column,
+                found,
+            } => AocError::parse(day, line, column, ParseErrorKind::UnexpectedChar(found)),
+        }
+    }
+}
+
+/// 1-based column at which `part` starts in `line`. `part` must be a subslice of `line`,
+/// which is always the case for the output of `split` and friends.
+pub fn column_of(line: &str, part: &str) -> usize {
+    let offset = (part.as_ptr() as usize).wrapping_sub(line.as_ptr() as usize);
+    if offset <= line.len() {
+        offset + 1
+    } else {
+        1
+    }
+}
+
+/// Parses `field`, a subslice of the single-line `line`, reporting errors at its column.
+pub fn parse_field<T>(day: u8, line: &str, field: &str) -> Result<T, AocError>
+where
+    T: FromStr<Err = ParseIntError>,
+{
+    field
+        .parse()
+        .map_err(|e| AocError::parse(day, 1, column_of(line, field), e))
+}
+
+/// Same as `parse_field` for a number that must be within `range`, which bounds the work done on
+/// the input. `expected` describes the range.
+pub fn parse_bounded<T>(
+    day: u8,
+    line: &str,
+    field: &str,
+    range: RangeInclusive<T>,
+    expected: &'static str,
+) -> Result<T, AocError>
+where
+    T: FromStr<Err = ParseIntError> + PartialOrd,
+{
+    let value = parse_field(day, line, field)?;
+    if range.contains(&value) {
+        Ok(value)
+    } else {
+        let kind = ParseErrorKind::Expected(expected);
+        Err(AocError::parse(day, 1, column_of(line, field), kind))
+    }
+}
+
+/// Splits `line` on `separator` into exactly two parts.
+pub fn split_pair<'a>(
+    day: u8,
+    line: &'a str,
+    separator: &str,
+    expected: &'static str,
+) -> Result<(&'a str, &'a str), AocError> {
+    line.split_once(separator)
+        .ok_or_else(|| AocError::parse(day, 1, line.len() + 1, ParseErrorKind::Expected(expected)))
+}
+
+/// Runs the single-line parser `f` on every line, carrying on after errors so that every faulty
+/// line is reported. Errors are moved to their line, as generators do.
+pub fn lint_lines<T>(input: &str, mut f: impl FnMut(&str) -> Result<T, AocError>) -> Vec<AocError> {
+    input
+        .lines()
+        .enumerate()
+        .filter_map(|(idx, l)| f(l).err().map(|e| e.at_line(idx + 1)))
+        .collect()
+}
+
+/// What a generator returns once it has carried on after errors to find them all: `value` if
+/// there was none, the first one otherwise
+pub fn first_error<T>((value, errors): (T, Vec<AocError>)) -> Result<T, AocError> {
+    match errors.into_iter().next() {
+        Some(e) => Err(e),
+        None => Ok(value),
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn should_locate_fields() {
+        let line = "forward 5x";
+        let err = parse_field::<u32>(2, line, &line[8..]).unwrap_err();
+        assert!(matches!(err, AocError::Parse { column: 9, .. }));
+        assert!(matches!(
+            err.at_line(3),
+            AocError::Parse {
+                day: 2,
+                line: 3,
+                column: 9,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn should_split_pairs() {
+        assert_eq!(
+            split_pair(5, "1,2 -> 3,4", " -> ", "` -> `"),
+            Ok(("1,2", "3,4"))
+        );
+        assert_eq!(
+            split_pair(5, "1,2", " -> ", "` -> `"),
+            Err(AocError::parse(5, 1, 4, ParseErrorKind::Expected("` -> `")))
+        );
+    }
+
+    #[test]
+    fn should_lint_every_line() {
+        let errors = lint_lines("1\nx\n3\n4y", |l| parse_field::<u32>(1, l, l));
+        let lines: Vec<usize> = errors
+            .iter()
+            .map(|e| match e {
+                AocError::Parse { line, .. } => *line,
+                _ => 0,
+            })
+            .collect();
+        assert_eq!(lines, vec![2, 4]);
+    }
+}
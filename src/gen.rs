@@ -0,0 +1,582 @@
+//! Seeded generators of puzzle inputs, to stress solvers with inputs bigger than the examples.
+//! The same seed and knobs always give the same text, which is accepted by the day's generator.
+//! Inputs stay within the value ranges of real puzzle inputs, so answers overflow for the same
+//! sizes as they would with real data.
+
+use crate::grid::Grid;
+use std::collections::BTreeSet;
+
+/// SplitMix64. Tiny, and unlike external crates its output is guaranteed to never change.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform-ish in `0..n`. The modulo bias is irrelevant for our sizes.
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "Empty range");
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Uniform-ish in `low..=high`
+    pub fn between(&mut self, low: usize, high: usize) -> usize {
+        low + self.below(high - low + 1)
+    }
+
+    /// True with probability `percent`%
+    pub fn chance(&mut self, percent: usize) -> bool {
+        self.below(100) < percent
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+fn join<T: ToString>(items: impl IntoIterator<Item = T>, separator: &str) -> String {
+    items
+        .into_iter()
+        .map(|x| x.to_string())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// `count` sonar depths, slowly going down
+pub fn day1(seed: u64, count: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let mut depth = rng.between(100, 200);
+    join(
+        (0..count).map(|_| {
+            depth = (depth + rng.below(51)).saturating_sub(20);
+            depth
+        }),
+        "\n",
+    )
+}
+
+/// `count` submarine instructions. The aim never goes negative, so part 2 overflows its `i32`
+/// past a few thousand instructions, like real inputs would.
+pub fn day2(seed: u64, count: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let mut aim = 0;
+    join(
+        (0..count).map(|_| {
+            let amount = rng.between(1, 9);
+            match rng.below(10) {
+                0..=4 => format!("forward {}", amount),
+                5..=6 if amount <= aim => {
+                    aim -= amount;
+                    format!("up {}", amount)
+                }
+                _ => {
+                    aim += amount;
+                    format!("down {}", amount)
+                }
+            }
+        }),
+        "\n",
+    )
+}
+
+/// At least `count` distinct `width`-bit numbers (`width` in `1..=15`).
+///
+/// The number with all bits set is always included so that the solver infers the right width,
+/// and numbers are added until the CO2 scrubber filter never ends up with candidates all sharing
+/// the considered bit, which real inputs avoid as it would discard every candidate.
+pub fn day3(seed: u64, width: u32, count: usize) -> String {
+    assert!((1..=15).contains(&width), "Unsupported width");
+    let mut rng = Rng::new(seed);
+    let all_ones = (1u16 << width) - 1;
+    let mut numbers: Vec<u16> = (0..all_ones).collect();
+    rng.shuffle(&mut numbers);
+    numbers.truncate(count.saturating_sub(1));
+    numbers.push(all_ones);
+    while let Some(missing) = unbalanced_co2_candidate(&numbers, width) {
+        numbers.push(missing);
+    }
+    rng.shuffle(&mut numbers);
+    join(
+        numbers
+            .iter()
+            .map(|x| format!("{:0width$b}", x, width = width as usize)),
+        "\n",
+    )
+}
+
+/// Runs the CO2 scrubber filter of day 3, returns a number to add if it gets stuck
+fn unbalanced_co2_candidate(numbers: &[u16], width: u32) -> Option<u16> {
+    let mut candidates = numbers.to_vec();
+    for bit in (0..width).rev() {
+        if candidates.len() == 1 {
+            return None;
+        }
+        let ones = candidates.iter().filter(|x| (*x >> bit) & 1 == 1).count();
+        let zeros = candidates.len() - ones;
+        if ones == 0 || zeros == 0 {
+            // Candidates are all the numbers sharing a prefix, so this one is new
+            return Some(candidates[0] ^ (1 << bit));
+        }
+        let keep = if ones >= zeros { 0 } else { 1 };
+        candidates.retain(|x| (*x >> bit) & 1 == keep);
+    }
+    None
+}
+
+/// All numbers from 0 to 99 are drawn so that every one of the `boards` wins eventually
+pub fn day4(seed: u64, boards: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let mut numbers: Vec<u32> = (0..100).collect();
+    rng.shuffle(&mut numbers);
+    let mut output = join(&numbers, ",");
+    for _ in 0..boards {
+        rng.shuffle(&mut numbers);
+        output += "\n";
+        for row in numbers[..25].chunks(5) {
+            output += "\n";
+            output += &join(row.iter().map(|x| format!("{:>2}", x)), " ");
+        }
+    }
+    output
+}
+
+/// `count` horizontal, vertical or diagonal vents with coordinates in `0..size`
+pub fn day5(seed: u64, count: usize, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    join(
+        (0..count).map(|_| {
+            let (x1, y1) = (rng.below(size), rng.below(size));
+            let (x2, y2) = match rng.below(3) {
+                0 => (rng.below(size), y1),
+                1 => (x1, rng.below(size)),
+                _ => {
+                    // Go as far as the grid allows in a random diagonal direction
+                    let (right, down) = (rng.chance(50), rng.chance(50));
+                    let max_x = if right { size - 1 - x1 } else { x1 };
+                    let max_y = if down { size - 1 - y1 } else { y1 };
+                    let length = rng.below(max_x.min(max_y) + 1);
+                    let x2 = if right { x1 + length } else { x1 - length };
+                    let y2 = if down { y1 + length } else { y1 - length };
+                    (x2, y2)
+                }
+            };
+            format!("{},{} -> {},{}", x1, y1, x2, y2)
+        }),
+        "\n",
+    )
+}
+
+/// `count` lanternfish timers between 1 and 5
+pub fn day6(seed: u64, count: usize) -> String {
+    let mut rng = Rng::new(seed);
+    join((0..count).map(|_| rng.between(1, 5)), ",")
+}
+
+/// `count` crab positions in `0..=max`
+pub fn day7(seed: u64, count: usize, max: usize) -> String {
+    let mut rng = Rng::new(seed);
+    join((0..count).map(|_| rng.below(max + 1)), ",")
+}
+
+const SEGMENTS: [&str; 10] = [
+    "abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg",
+];
+
+/// `count` notes, each with its own random wiring
+pub fn day8(seed: u64, count: usize) -> String {
+    let mut rng = Rng::new(seed);
+    join(
+        (0..count).map(|_| {
+            let digits: Vec<usize> = (0..4).map(|_| rng.below(10)).collect();
+            let mut wiring: Vec<char> = "abcdefg".chars().collect();
+            rng.shuffle(&mut wiring);
+            let mut pattern = |digit: usize| {
+                let mut wires: Vec<char> = SEGMENTS[digit]
+                    .chars()
+                    .map(|c| wiring[(c as u8 - b'a') as usize])
+                    .collect();
+                rng.shuffle(&mut wires);
+                wires.into_iter().collect::<String>()
+            };
+            let mut patterns: Vec<String> = (0..10).map(&mut pattern).collect();
+            let outputs: Vec<String> = digits.into_iter().map(&mut pattern).collect();
+            rng.shuffle(&mut patterns);
+            format!("{} | {}", patterns.join(" "), outputs.join(" "))
+        }),
+        "\n",
+    )
+}
+
+/// Heightmap where about 45% of cells are 9, which keeps basins small enough for the
+/// recursive flood fill of the solver
+pub fn day9(seed: u64, width: usize, height: usize) -> String {
+    let mut rng = Rng::new(seed);
+    Grid::from_fn(
+        width,
+        height,
+        |_| {
+            if rng.chance(45) {
+                9
+            } else {
+                rng.below(9)
+            }
+        },
+    )
+    .to_string()
+}
+
+/// `count` lines of at most `length` chars, opening at most `max_depth` (up to 25) chunks.
+///
+/// Lines are either corrupted or incomplete, the number of incomplete ones being odd so that
+/// part 2 has a middle score.
+pub fn day10(seed: u64, count: usize, length: usize, max_depth: usize) -> String {
+    const OPENING: [char; 4] = ['(', '[', '{', '<'];
+    const CLOSING: [char; 4] = [')', ']', '}', '>'];
+    let mut rng = Rng::new(seed);
+    let max_depth = max_depth.clamp(1, 25);
+    let mut incomplete: Vec<bool> = (0..count).map(|_| rng.chance(50)).collect();
+    if incomplete.iter().filter(|x| **x).count() % 2 == 0 {
+        if let Some(first) = incomplete.first_mut() {
+            *first = !*first;
+        }
+    }
+    join(
+        incomplete.into_iter().map(|incomplete| {
+            let mut line = String::new();
+            let mut stack = Vec::new();
+            for _ in 0..length.saturating_sub(1) {
+                if !stack.is_empty() && (stack.len() >= max_depth || rng.chance(50)) {
+                    line.push(CLOSING[stack.pop().unwrap()]); // safe because stack isn't empty
+                } else {
+                    let chunk = rng.below(4);
+                    stack.push(chunk);
+                    line.push(OPENING[chunk]);
+                }
+            }
+            match stack.last() {
+                None => line.push(*rng.choose(&OPENING)),
+                Some(&chunk) if !incomplete => {
+                    line.push(CLOSING[(chunk + rng.between(1, 3)) % 4]);
+                }
+                Some(_) => (),
+            }
+            line
+        }),
+        "\n",
+    )
+}
+
+/// `size`x`size` octopuses that all flash at once at some point, as part 2 would never end
+/// otherwise. Energies are `spread` (up to 10) consecutive values: narrow spreads synchronize
+/// in a few steps, wide ones take longer and may not synchronize at all, in which case the
+/// spread is narrowed until it does.
+pub fn day11(seed: u64, size: usize, spread: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let mut spread = spread.clamp(1, 10);
+    loop {
+        for _ in 0..10 {
+            let base = rng.below(11 - spread);
+            let octopuses = Grid::from_fn(size, size, |_| (base + rng.below(spread)) as u8);
+            if synchronizes(&octopuses, 1_000) {
+                return octopuses.to_string();
+            }
+        }
+        // A spread of one always synchronizes
+        spread -= 1;
+    }
+}
+
+/// Simulates octopus flashes for up to `steps` steps, returns whether they all flash at once
+fn synchronizes(octopuses: &Grid<u8>, steps: usize) -> bool {
+    let mut octopuses = octopuses.clone();
+    for _ in 0..steps {
+        let mut to_flash = Vec::new();
+        for pos in octopuses.positions() {
+            octopuses[pos] += 1;
+            if octopuses[pos] == 10 {
+                to_flash.push(pos);
+            }
+        }
+        let mut flashes = 0;
+        while let Some(pos) = to_flash.pop() {
+            flashes += 1;
+            let neighbors: Vec<_> = octopuses.all_neighbors(pos).collect();
+            for p in neighbors {
+                octopuses[p] += 1;
+                if octopuses[p] == 10 {
+                    to_flash.push(p);
+                }
+            }
+        }
+        if flashes == octopuses.len() {
+            return true;
+        }
+        octopuses
+            .iter_mut()
+            .filter(|o| **o >= 10)
+            .for_each(|o| *o = 0);
+    }
+    false
+}
+
+/// Cave system with `small` and `big` caves (up to 676 of each) and `extra` links on top of a
+/// spanning tree. Big caves are never linked together, otherwise there would be infinitely many
+/// paths. The number of paths, hence the solving time, grows exponentially with the size.
+pub fn day12(seed: u64, small: usize, big: usize, extra: usize) -> String {
+    assert!(small <= 676 && big <= 676, "Too many caves");
+    let mut rng = Rng::new(seed);
+    let name = |i: usize| {
+        format!(
+            "{}{}",
+            (b'a' + (i / 26) as u8) as char,
+            (b'a' + (i % 26) as u8) as char
+        )
+    };
+    let mut caves: Vec<String> = (0..small)
+        .map(name)
+        .chain((0..big).map(|i| name(i).to_uppercase()))
+        .collect();
+    caves.push("end".to_string());
+    rng.shuffle(&mut caves);
+    let is_big = |cave: &String| cave.starts_with(|c: char| c.is_ascii_uppercase());
+    // A small cave first, so that big caves always have something to link to in the tree
+    let first_small = caves.iter().position(|c| !is_big(c)).unwrap(); // "end" is small
+    caves.swap(0, first_small);
+
+    let mut links = BTreeSet::new();
+    let mut link = |a: usize, b: usize, caves: &[String]| {
+        if a != b && !(is_big(&caves[a]) && is_big(&caves[b])) {
+            links.insert((a.min(b), a.max(b)));
+        }
+    };
+    // Spanning tree, so that every cave can be reached without going back to start
+    for i in 1..caves.len() {
+        let j = match is_big(&caves[i]) {
+            true => *rng.choose(&(0..i).filter(|&j| !is_big(&caves[j])).collect::<Vec<_>>()),
+            false => rng.below(i),
+        };
+        link(i, j, &caves);
+    }
+    for _ in 0..extra {
+        link(rng.below(caves.len()), rng.below(caves.len()), &caves);
+    }
+    caves.push("start".to_string());
+    for _ in 0..rng.between(1, 3) {
+        link(caves.len() - 1, rng.below(caves.len() - 1), &caves);
+    }
+
+    let mut links: Vec<(usize, usize)> = links.into_iter().collect();
+    rng.shuffle(&mut links);
+    join(
+        links.into_iter().map(|(a, b)| match rng.chance(50) {
+            true => format!("{}-{}", caves[a], caves[b]),
+            false => format!("{}-{}", caves[b], caves[a]),
+        }),
+        "\n",
+    )
+}
+
+/// `points` dots and `folds` fold instructions, alternating between `x` and `y`.
+///
+/// The paper is built backwards from a 40x6 code area, unfolding it and mirroring each dot at
+/// random, so that no dot ever lies on a fold line.
+pub fn day13(seed: u64, points: usize, folds: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let (mut width, mut height) = (40, 6);
+    let mut lines = Vec::new();
+    for i in 0..folds {
+        // The last fold is along y, like in real inputs
+        if i % 2 == 1 {
+            lines.push(('x', width));
+            width = width * 2 + 1;
+        } else {
+            lines.push(('y', height));
+            height = height * 2 + 1;
+        }
+    }
+    let dots = (0..points).map(|_| {
+        let (mut x, mut y) = (rng.below(40), rng.below(6));
+        for &(axis, line) in &lines {
+            if rng.chance(50) {
+                match axis {
+                    'x' => x = 2 * line - x,
+                    _ => y = 2 * line - y,
+                }
+            }
+        }
+        format!("{},{}", x, y)
+    });
+    let dots = join(dots, "\n");
+    let folds = lines
+        .iter()
+        .rev()
+        .map(|(axis, line)| format!("fold along {}={}", axis, line));
+    format!("{}\n\n{}", dots, join(folds, "\n"))
+}
+
+/// Template of `length` (at least 2) elements out of `elements` (between 2 and 26) distinct ones,
+/// with a rule for every pair
+pub fn day14(seed: u64, elements: usize, length: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let mut letters: Vec<char> = ('A'..='Z').collect();
+    rng.shuffle(&mut letters);
+    letters.truncate(elements.clamp(2, 26));
+    let template: String = (0..length.max(2)).map(|_| *rng.choose(&letters)).collect();
+    let rules = letters
+        .iter()
+        .flat_map(|&a| letters.iter().map(move |&b| (a, b)));
+    let rules: Vec<String> = rules
+        .map(|(a, b)| format!("{}{} -> {}", a, b, rng.choose(&letters)))
+        .collect();
+    format!("{}\n\n{}", template, rules.join("\n"))
+}
+
+/// Risk levels between 1 and 9
+pub fn day15(seed: u64, width: usize, height: usize) -> String {
+    let mut rng = Rng::new(seed);
+    Grid::from_fn(width, height, |_| rng.between(1, 9)).to_string()
+}
+
+/// BITS transmission of a packet tree up to `depth` levels deep.
+///
+/// Operators only get operands for which they don't overflow a `u64`, which the solver doesn't
+/// handle, falling back to `min` and `max` otherwise.
+pub fn day16(seed: u64, depth: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let (mut bits, _) = packet(&mut rng, depth);
+    while bits.len() % 4 != 0 {
+        bits.push('0');
+    }
+    join(
+        bits.as_bytes().chunks(4).map(|nibble| {
+            let value = nibble.iter().fold(0, |acc, b| acc * 2 + (b - b'0'));
+            format!("{:X}", value)
+        }),
+        "",
+    )
+}
+
+/// Returns a random packet as a string of bits, along with its value
+fn packet(rng: &mut Rng, depth: usize) -> (String, u64) {
+    let version = rng.below(8);
+    if depth == 0 || rng.chance(30) {
+        let width = rng.between(1, 16);
+        let value = rng.below(1 << width) as u64;
+        let groups = format!("{:b}", value).len().div_ceil(4);
+        let mut bits = format!("{:03b}100", version);
+        for group in (0..groups).rev() {
+            let more = if group == 0 { 0 } else { 1 };
+            bits += &format!("{}{:04b}", more, (value >> (group * 4)) & 0xF);
+        }
+        return (bits, value);
+    }
+
+    let mut kind = rng.below(7);
+    if kind >= 4 {
+        // Type 4 is for literals
+        kind += 1;
+    }
+    let count = if kind >= 5 { 2 } else { rng.between(1, 4) };
+    let (sub_packets, values): (Vec<String>, Vec<u64>) =
+        (0..count).map(|_| packet(rng, depth - 1)).unzip();
+    let min = *values.iter().min().unwrap(); // safe because count > 0
+    let max = *values.iter().max().unwrap();
+    let (kind, value) = match kind {
+        0 => values
+            .iter()
+            .try_fold(0u64, |acc, x| acc.checked_add(*x))
+            .map_or((2, min), |sum| (0, sum)),
+        1 => values
+            .iter()
+            .try_fold(1u64, |acc, x| acc.checked_mul(*x))
+            .map_or((3, max), |product| (1, product)),
+        2 => (2, min),
+        3 => (3, max),
+        5 => (5, (values[0] > values[1]) as u64),
+        6 => (6, (values[0] < values[1]) as u64),
+        _ => (7, (values[0] == values[1]) as u64),
+    };
+
+    let sub_packets = sub_packets.concat();
+    let mut bits = format!("{:03b}{:03b}", version, kind);
+    if sub_packets.len() < 1 << 15 && rng.chance(50) {
+        bits += &format!("0{:015b}", sub_packets.len());
+    } else {
+        bits += &format!("1{:011b}", count);
+    }
+    (bits + &sub_packets, value)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::error::AocError;
+    use crate::*;
+
+    #[test]
+    fn should_be_deterministic() {
+        assert_eq!(day5(42, 100, 50), day5(42, 100, 50));
+        assert_ne!(day5(42, 100, 50), day5(43, 100, 50));
+        assert_eq!(day16(7, 6), day16(7, 6));
+    }
+
+    #[test]
+    fn should_be_accepted_by_solvers() -> Result<(), AocError> {
+        for seed in 0..5 {
+            day1::part2(&day1::ints(&day1(seed, 500))?);
+            day2::part2(&day2::parse(&day2(seed, 500))?)?;
+            day3::part2(&day3::parse(&day3(seed, 12, 500))?)?;
+            day4::part2(&day4::parse(&day4(seed, 50))?)?;
+            day5::part2(&day5::parse(&day5(seed, 200, 100))?);
+            day6::part2(&day6::parse(&day6(seed, 300))?)?;
+            day7::part2(&day7::parse(&day7(seed, 300, 1_000))?)?;
+            day8::part2(&day8::parse(&day8(seed, 100))?)?;
+            day9::part2(&day9::parse(&day9(seed, 50, 40))?);
+            day10::part2(&day10::parse(&day10(seed, 50, 80, 20))?)?;
+            day11::part2(&day11::parse(&day11(seed, 15, 6))?)?;
+            day12::part2(&day12::parse(&day12(seed, 5, 2, 3))?)?;
+            day13::part2(&day13::parse(&day13(seed, 300, 6))?);
+            day14::part2(&day14::parse(&day14(seed, 6, 20))?)?;
+            day15::part2(&day15::parse(&day15(seed, 30, 20))?);
+            day16::part2(&day16::parse(&day16(seed, 6))?)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn should_keep_co2_filter_balanced() {
+        for seed in 0..20 {
+            let numbers: Vec<u16> = day3(seed, 8, 30)
+                .lines()
+                .map(|l| u16::from_str_radix(l, 2).unwrap())
+                .collect();
+            assert!(numbers.len() >= 30);
+            assert_eq!(unbalanced_co2_candidate(&numbers, 8), None);
+        }
+    }
+
+    #[test]
+    fn should_encode_packets() -> Result<(), AocError> {
+        for seed in 0..50 {
+            let mut rng = Rng::new(seed);
+            let (bits, value) = packet(&mut rng, 5);
+            assert_eq!(day16::part2(&bits)?, value);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,358 @@
+//! Plane geometry shared by the puzzles drawn on a 2D map: points, bounding boxes and segments.
+//! `x` grows to the right and `y` downwards, as in the puzzle drawings and in `Grid`.
+
+use core::fmt;
+use core::ops::{Add, AddAssign, Div, Mul, Neg, Rem, Sub, SubAssign};
+
+/// Integers usable as coordinates
+pub trait Coord:
+    Copy
+    + Ord
+    + fmt::Debug
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    /// Unsigned integers of the same size, holding the distance between any two coordinates
+    type Distance: Coord;
+
+    fn abs_diff(self, other: Self) -> Self::Distance;
+
+    /// `self` moved by `distance` towards `to`, which it mustn't go past
+    fn step_towards(self, to: Self, distance: Self::Distance) -> Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! coord {
+    ($($t:ty => $d:ty),*) => {
+        $(impl Coord for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            type Distance = $d;
+
+            fn abs_diff(self, other: Self) -> $d {
+                <$t>::abs_diff(self, other)
+            }
+
+            #[allow(clippy::unnecessary_cast)]
+            fn step_towards(self, to: Self, distance: $d) -> Self {
+                // The result is between `self` and `to`, so the two's complement arithmetic
+                // on the unsigned type is exact
+                if self < to {
+                    (self as $d).wrapping_add(distance) as $t
+                } else {
+                    (self as $d).wrapping_sub(distance) as $t
+                }
+            }
+
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$t>::checked_add(self, other)
+            }
+        })*
+    };
+}
+
+coord!(
+    i8 => u8, i16 => u16, i32 => u32, i64 => u64, isize => usize,
+    u8 => u8, u16 => u16, u32 => u32, u64 => u64, usize => usize
+);
+
+fn gcd<T: Coord>(a: T, b: T) -> T {
+    if b == T::ZERO {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Point2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T> Point2<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Point2 { x, y }
+    }
+}
+
+impl<T: Coord> Point2<T> {
+    /// Number of orthogonal steps between the two points, if it fits `T::Distance`
+    pub fn manhattan(self, other: Self) -> Option<T::Distance> {
+        self.x
+            .abs_diff(other.x)
+            .checked_add(self.y.abs_diff(other.y))
+    }
+
+    /// Number of steps between the two points when diagonal steps are allowed
+    pub fn chebyshev(self, other: Self) -> T::Distance {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+}
+
+impl<T> From<(T, T)> for Point2<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Point2 { x, y }
+    }
+}
+
+impl<T> From<Point2<T>> for (T, T) {
+    fn from(p: Point2<T>) -> Self {
+        (p.x, p.y)
+    }
+}
+
+impl<T: Add<Output = T>> Add for Point2<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Point2::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Point2<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Point2::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Point2<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Point2::new(-self.x, -self.y)
+    }
+}
+
+/// Scaling by a factor
+impl<T: Copy + Mul<Output = T>> Mul<T> for Point2<T> {
+    type Output = Self;
+
+    fn mul(self, factor: T) -> Self {
+        Point2::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl<T: Copy + Add<Output = T>> AddAssign for Point2<T> {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl<T: Copy + Sub<Output = T>> SubAssign for Point2<T> {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+/// Displayed as `x,y`, as in the puzzle inputs
+impl<T: fmt::Display> fmt::Display for Point2<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+/// Smallest rectangle holding a set of points, both corners included
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bounds<T> {
+    pub min: Point2<T>,
+    pub max: Point2<T>,
+}
+
+impl<T: Coord> Bounds<T> {
+    /// Bounding box of `points`, if there is any
+    pub fn of(points: impl IntoIterator<Item = Point2<T>>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        let mut bounds = Bounds {
+            min: first,
+            max: first,
+        };
+        points.for_each(|p| bounds.extend(p));
+        Some(bounds)
+    }
+
+    /// Grows the box to hold `p`
+    pub fn extend(&mut self, p: Point2<T>) {
+        self.min = Point2::new(self.min.x.min(p.x), self.min.y.min(p.y));
+        self.max = Point2::new(self.max.x.max(p.x), self.max.y.max(p.y));
+    }
+
+    pub fn contains(&self, p: Point2<T>) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x) && (self.min.y..=self.max.y).contains(&p.y)
+    }
+
+    /// Number of columns in the box, if it fits `T::Distance`, which it doesn't when the box
+    /// spans every coordinate
+    pub fn width(&self) -> Option<T::Distance> {
+        self.max.x.abs_diff(self.min.x).checked_add(Coord::ONE)
+    }
+
+    /// Number of rows in the box, if it fits `T::Distance`
+    pub fn height(&self) -> Option<T::Distance> {
+        self.max.y.abs_diff(self.min.y).checked_add(Coord::ONE)
+    }
+}
+
+/// A straight line between two points, both included
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Segment<T> {
+    pub start: Point2<T>,
+    pub end: Point2<T>,
+}
+
+impl<T: Coord> Segment<T> {
+    pub fn new(start: Point2<T>, end: Point2<T>) -> Self {
+        Segment { start, end }
+    }
+
+    pub fn is_horizontal(&self) -> bool {
+        self.start.y == self.end.y
+    }
+
+    pub fn is_vertical(&self) -> bool {
+        self.start.x == self.end.x
+    }
+
+    /// Whether the segment is at exactly 45 degrees
+    pub fn is_diagonal(&self) -> bool {
+        self.start.x.abs_diff(self.end.x) == self.start.y.abs_diff(self.end.y)
+    }
+
+    pub fn bounds(&self) -> Bounds<T> {
+        let mut bounds = Bounds {
+            min: self.start,
+            max: self.start,
+        };
+        bounds.extend(self.end);
+        bounds
+    }
+
+    /// Points with integer coordinates on the segment, from `start` to `end`
+    pub fn points(&self) -> LatticePoints<T> {
+        let (dx, dy) = (
+            self.start.x.abs_diff(self.end.x),
+            self.start.y.abs_diff(self.end.y),
+        );
+        // Both are 0 when the segment is a single point
+        let steps = gcd(dx, dy);
+        let step = if steps == Coord::ZERO {
+            Point2::new(Coord::ZERO, Coord::ZERO)
+        } else {
+            Point2::new(dx / steps, dy / steps)
+        };
+        LatticePoints {
+            next: Some(self.start),
+            end: self.end,
+            step,
+        }
+    }
+}
+
+/// Iterator over the lattice points of a `Segment`
+#[derive(Debug, Clone)]
+pub struct LatticePoints<T: Coord> {
+    next: Option<Point2<T>>,
+    end: Point2<T>,
+    /// Distance between two points along each axis, the direction being towards `end`
+    step: Point2<T::Distance>,
+}
+
+impl<T: Coord> Iterator for LatticePoints<T> {
+    type Item = Point2<T>;
+
+    fn next(&mut self) -> Option<Point2<T>> {
+        let current = self.next?;
+        self.next = (current != self.end).then(|| {
+            Point2::new(
+                current.x.step_towards(self.end.x, self.step.x),
+                current.y.step_towards(self.end.y, self.step.y),
+            )
+        });
+        Some(current)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn should_compute_distances() {
+        let (a, b) = (Point2::new(1i32, -2), Point2::new(-3, 5));
+        assert_eq!(a + b, Point2::new(-2, 3));
+        assert_eq!(b - a, Point2::new(-4, 7));
+        assert_eq!(-a * 2, Point2::new(-2, 4));
+        assert_eq!(a.manhattan(b), Some(11));
+        assert_eq!(a.chebyshev(b), 7);
+        assert_eq!(Point2::new(2usize, 0).manhattan(Point2::new(0, 3)), Some(5));
+
+        let (min, max) = (Point2::new(i32::MIN, i32::MIN), Point2::new(i32::MAX, 0));
+        assert_eq!(min.chebyshev(max), u32::MAX);
+        assert_eq!(min.manhattan(max), None);
+        assert_eq!(
+            min.manhattan(Point2::new(i32::MAX, i32::MIN)),
+            Some(u32::MAX)
+        );
+        assert_eq!(a.to_string(), "1,-2");
+    }
+
+    #[test]
+    fn should_bound_points() {
+        let bounds = Bounds::of([(3i32, 1), (0, 4), (2, 2)].map(Point2::from)).unwrap();
+        assert_eq!(bounds.min, Point2::new(0, 1));
+        assert_eq!(bounds.max, Point2::new(3, 4));
+        assert_eq!((bounds.width(), bounds.height()), (Some(4), Some(4)));
+        assert!(bounds.contains(Point2::new(3, 4)));
+        assert!(!bounds.contains(Point2::new(1, 0)));
+        assert_eq!(Bounds::<i32>::of([]), None);
+
+        let bounds = Bounds::of([(i8::MIN, 0), (i8::MAX, -1)].map(Point2::from)).unwrap();
+        assert_eq!((bounds.width(), bounds.height()), (None, Some(2)));
+        let bounds = Bounds::of([(1u8, 0), (u8::MAX, 0)].map(Point2::from)).unwrap();
+        assert_eq!(bounds.width(), Some(u8::MAX));
+    }
+
+    #[test]
+    fn should_iterate_lattice_points() {
+        let points = |start: (u32, u32), end: (u32, u32)| -> Vec<(u32, u32)> {
+            Segment::new(start.into(), end.into())
+                .points()
+                .map(Into::into)
+                .collect()
+        };
+        assert_eq!(points((1, 1), (1, 3)), vec![(1, 1), (1, 2), (1, 3)]);
+        assert_eq!(points((9, 7), (7, 9)), vec![(9, 7), (8, 8), (7, 9)]);
+        assert_eq!(points((0, 6), (4, 0)), vec![(0, 6), (2, 3), (4, 0)]);
+        assert_eq!(points((2, 2), (2, 2)), vec![(2, 2)]);
+
+        let segment = Segment::new(Point2::new(0, 0), Point2::new(-3, 3));
+        assert!(segment.is_diagonal() && !segment.is_horizontal());
+        assert_eq!(segment.points().count(), 4);
+        assert_eq!(segment.bounds().min, Point2::new(-3, 0));
+
+        let segment = Segment::new(Point2::new(i32::MIN, 0), Point2::new(i32::MAX, 1));
+        assert!(!segment.is_diagonal());
+        assert_eq!(
+            segment.points().collect::<Vec<_>>(),
+            [segment.start, segment.end]
+        );
+        let segment = Segment::new(Point2::new(i8::MAX, i8::MIN), Point2::new(i8::MIN, i8::MAX));
+        assert!(segment.is_diagonal());
+        assert_eq!(segment.points().count(), 256);
+        assert_eq!(segment.points().last(), Some(segment.end));
+    }
+}
@@ -0,0 +1,436 @@
+//! Flat-storage 2D grid shared by the "map of digits" puzzles.
+//! Positions are `Point2`s, `x` being the column and `y` the row.
+
+use crate::geom::Point2;
+use std::fmt;
+use std::ops::{Index, IndexMut};
+use thiserror::Error;
+
+pub type Position = Point2<usize>;
+
+const ORTHOGONAL: [(isize, isize); 4] = [(-1, 0), (0, -1), (1, 0), (0, 1)];
+const DIAGONAL: [(isize, isize); 4] = [(-1, -1), (1, -1), (-1, 1), (1, 1)];
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum GridParseError {
+    #[error("Grid is empty")]
+    Empty,
+    #[error("Line {line} has {found} cells, expected {expected}")]
+    RaggedRow {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    #[error("Invalid cell {found:?} at line {line}, column {column}")]
+    InvalidCell {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "RawGrid<T>")
+)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+/// Fields of a deserialized `Grid`, which may not agree with each other
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct RawGrid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+#[cfg(feature = "serde")]
+impl<T> TryFrom<RawGrid<T>> for Grid<T> {
+    type Error = &'static str;
+
+    fn try_from(raw: RawGrid<T>) -> Result<Self, Self::Error> {
+        Grid::from_parts(raw.width, raw.height, raw.cells).ok_or("Invalid grid dimensions")
+    }
+}
+
+impl<T> Grid<T> {
+    /// Builds a grid from row-major cells. Panics if `cells` can't be split in rows of `width`.
+    pub fn from_vec(width: usize, cells: Vec<T>) -> Self {
+        assert!(
+            width > 0 && cells.len().is_multiple_of(width),
+            "Invalid grid dimensions"
+        );
+        let height = cells.len() / width;
+        Grid {
+            cells,
+            width,
+            height,
+        }
+    }
+
+    /// Same as `from_vec` with the height too, `None` if `width` is 0 or there aren't
+    /// `width * height` cells
+    pub fn from_parts(width: usize, height: usize, cells: Vec<T>) -> Option<Self> {
+        (width > 0 && width.checked_mul(height) == Some(cells.len())).then_some(Grid {
+            cells,
+            width,
+            height,
+        })
+    }
+
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(Position) -> T) -> Self {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| Point2::new(x, y)))
+            .map(&mut f)
+            .collect();
+        Grid {
+            cells,
+            width,
+            height,
+        }
+    }
+
+    /// Parses one row per line, mapping each char to a cell.
+    /// Line and column numbers in errors are 1-based.
+    pub fn parse_with(
+        input: &str,
+        mut f: impl FnMut(char) -> Option<T>,
+    ) -> Result<Self, GridParseError> {
+        let mut cells = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+        for (idx, line) in input.lines().enumerate() {
+            let before = cells.len();
+            for (col, c) in line.chars().enumerate() {
+                let cell = f(c).ok_or(GridParseError::InvalidCell {
+                    line: idx + 1,
+                    column: col + 1,
+                    found: c,
+                })?;
+                cells.push(cell);
+            }
+            let found = cells.len() - before;
+            match width {
+                None => width = Some(found),
+                Some(expected) if expected != found => {
+                    return Err(GridParseError::RaggedRow {
+                        line: idx + 1,
+                        expected,
+                        found,
+                    })
+                }
+                Some(_) => (),
+            }
+            height += 1;
+        }
+        match width {
+            Some(width) if width > 0 => Ok(Grid {
+                cells,
+                width,
+                height,
+            }),
+            _ => Err(GridParseError::Empty),
+        }
+    }
+
+    /// Same checks as `parse_with`, carrying on after faulty lines: each one is reported with its
+    /// first invalid cell and its number of cells if it differs from the first line's.
+    pub fn lint_with(input: &str, mut f: impl FnMut(char) -> Option<T>) -> Vec<GridParseError> {
+        let mut errors = Vec::new();
+        let mut width = None;
+        for (idx, line) in input.lines().enumerate() {
+            let invalid = line.chars().enumerate().find(|&(_, c)| f(c).is_none());
+            if let Some((col, c)) = invalid {
+                errors.push(GridParseError::InvalidCell {
+                    line: idx + 1,
+                    column: col + 1,
+                    found: c,
+                });
+            }
+            let found = line.chars().count();
+            match width {
+                None => width = Some(found),
+                Some(expected) if expected != found => errors.push(GridParseError::RaggedRow {
+                    line: idx + 1,
+                    expected,
+                    found,
+                }),
+                Some(_) => (),
+            }
+        }
+        if width.unwrap_or(0) == 0 {
+            errors.push(GridParseError::Empty);
+        }
+        errors
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, pos: Position) -> bool {
+        pos.x < self.width && pos.y < self.height
+    }
+
+    pub fn get(&self, pos: Position) -> Option<&T> {
+        if self.contains(pos) {
+            Some(&self.cells[pos.y * self.width + pos.x])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, pos: Position) -> Option<&mut T> {
+        if self.contains(pos) {
+            Some(&mut self.cells[pos.y * self.width + pos.x])
+        } else {
+            None
+        }
+    }
+
+    /// All positions, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| Point2::new(x, y)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.cells.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> + '_ {
+        self.cells.iter_mut()
+    }
+
+    /// Cells along with their position, row by row.
+    pub fn enumerate(&self) -> impl Iterator<Item = (Position, &T)> + '_ {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> + '_ {
+        self.cells.chunks(self.width)
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> + '_ {
+        assert!(x < self.width, "Column out of bounds");
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T> + '_> + '_ {
+        (0..self.width).map(move |x| self.column(x))
+    }
+
+    /// Up to 4 neighbors sharing an edge with `pos`.
+    pub fn orthogonal_neighbors(&self, pos: Position) -> impl Iterator<Item = Position> + '_ {
+        self.offsets(pos, ORTHOGONAL)
+    }
+
+    /// Up to 4 neighbors sharing only a corner with `pos`.
+    pub fn diagonal_neighbors(&self, pos: Position) -> impl Iterator<Item = Position> + '_ {
+        self.offsets(pos, DIAGONAL)
+    }
+
+    /// Up to 8 neighbors, orthogonal ones first.
+    pub fn all_neighbors(&self, pos: Position) -> impl Iterator<Item = Position> + '_ {
+        self.orthogonal_neighbors(pos)
+            .chain(self.diagonal_neighbors(pos))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    fn offsets(
+        &self,
+        pos: Position,
+        offsets: [(isize, isize); 4],
+    ) -> impl Iterator<Item = Position> + '_ {
+        offsets.into_iter().filter_map(move |(dx, dy)| {
+            let pos = Point2::new(pos.x.checked_add_signed(dx)?, pos.y.checked_add_signed(dy)?);
+            self.contains(pos).then_some(pos)
+        })
+    }
+}
+
+impl Grid<u8> {
+    /// Parses a map of decimal digits such as `2199943210`.
+    pub fn parse_digits(input: &str) -> Result<Self, GridParseError> {
+        Grid::parse_with(input, digit)
+    }
+
+    pub fn lint_digits(input: &str) -> Vec<GridParseError> {
+        Grid::lint_with(input, digit)
+    }
+}
+
+fn digit(c: char) -> Option<u8> {
+    c.to_digit(10).map(|d| d as u8)
+}
+
+impl Grid<char> {
+    pub fn parse_chars(input: &str) -> Result<Self, GridParseError> {
+        Grid::parse_with(input, Some)
+    }
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Position) -> &T {
+        self.get(pos).expect("Position out of bounds")
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, pos: Position) -> &mut T {
+        self.get_mut(pos).expect("Position out of bounds")
+    }
+}
+
+/// Displays rows on separate lines, without any separator between cells.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    const INPUT: &str = "123
+456";
+
+    #[test]
+    fn should_parse_digits() -> Result<(), GridParseError> {
+        let grid = Grid::parse_digits(INPUT)?;
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 2);
+        assert_eq!(grid[Point2::new(0, 1)], 4);
+        assert_eq!(grid.get(Point2::new(3, 0)), None);
+        assert_eq!(grid.row(1), &[4, 5, 6]);
+        assert_eq!(grid.column(2).copied().collect::<Vec<_>>(), vec![3, 6]);
+        assert_eq!(grid.to_string(), INPUT);
+        Ok(())
+    }
+
+    #[test]
+    fn should_reject_bad_input() {
+        assert_eq!(Grid::parse_digits(""), Err(GridParseError::Empty));
+        assert_eq!(
+            Grid::parse_digits("12\n3"),
+            Err(GridParseError::RaggedRow {
+                line: 2,
+                expected: 2,
+                found: 1
+            })
+        );
+        assert_eq!(
+            Grid::parse_digits("12\n3x"),
+            Err(GridParseError::InvalidCell {
+                line: 2,
+                column: 2,
+                found: 'x'
+            })
+        );
+    }
+
+    #[test]
+    fn should_lint_every_line() {
+        assert_eq!(Grid::lint_digits("123\n456"), vec![]);
+        assert_eq!(Grid::lint_digits(""), vec![GridParseError::Empty]);
+        assert_eq!(
+            Grid::lint_digits("1x3\n45\n7y9"),
+            vec![
+                GridParseError::InvalidCell {
+                    line: 1,
+                    column: 2,
+                    found: 'x'
+                },
+                GridParseError::RaggedRow {
+                    line: 2,
+                    expected: 3,
+                    found: 2
+                },
+                GridParseError::InvalidCell {
+                    line: 3,
+                    column: 2,
+                    found: 'y'
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_find_neighbors() -> Result<(), GridParseError> {
+        let grid = Grid::parse_digits(INPUT)?;
+        let neighbors = |found: &mut dyn Iterator<Item = Position>| -> Vec<(usize, usize)> {
+            found.map(Into::into).collect()
+        };
+        let corner = neighbors(&mut grid.orthogonal_neighbors(Point2::new(0, 0)));
+        assert_eq!(corner, vec![(1, 0), (0, 1)]);
+        let middle = neighbors(&mut grid.all_neighbors(Point2::new(1, 0)));
+        assert_eq!(middle, vec![(0, 0), (2, 0), (1, 1), (0, 1), (2, 1)]);
+        let diagonal = neighbors(&mut grid.diagonal_neighbors(Point2::new(2, 1)));
+        assert_eq!(diagonal, vec![(1, 0)]);
+        Ok(())
+    }
+
+    #[test]
+    fn should_check_dimensions() {
+        assert_eq!(Grid::from_parts(2, 2, vec![1, 2, 3]), None);
+        assert_eq!(Grid::from_parts(usize::MAX, 2, vec![1, 2]), None);
+        assert_eq!(Grid::<u8>::from_parts(0, 2, vec![]), None);
+        assert_eq!(
+            Grid::from_parts(2, 1, vec![1, 2]),
+            Some(Grid::from_vec(2, vec![1, 2]))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn should_deserialize_consistent_grids() -> Result<(), serde_json::Error> {
+        let grid = Grid::from_vec(2, vec![1, 2, 3, 4]);
+        let json = serde_json::to_string(&grid)?;
+        assert_eq!(serde_json::from_str::<Grid<u8>>(&json)?, grid);
+        let json = r#"{"cells":[1,2,3],"width":2,"height":2}"#;
+        assert!(serde_json::from_str::<Grid<u8>>(json).is_err());
+        let json = r#"{"cells":[],"width":0,"height":2}"#;
+        assert!(serde_json::from_str::<Grid<u8>>(json).is_err());
+        Ok(())
+    }
+}
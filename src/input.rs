@@ -0,0 +1,103 @@
+//! Normalization of puzzle inputs, done by every day's `parse` before anything else, so that
+//! inputs saved on Windows or pasted from a browser parse like the ones downloaded by `cargo aoc`.
+
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+
+const BOM: char = '\u{feff}';
+
+/// Strips the BOM, turns CRLF line endings into LF, trims trailing whitespace from every line and
+/// drops trailing blank lines. Borrows `input` when it's already normalized.
+pub fn normalize(input: &str) -> Cow<'_, str> {
+    let input = input.strip_prefix(BOM).unwrap_or(input).trim_end();
+    if input.split('\n').all(|line| line.trim_end() == line) {
+        Cow::Borrowed(input)
+    } else {
+        Cow::Owned(
+            input
+                .split('\n')
+                .map(str::trim_end)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+    }
+}
+
+/// Same as `normalize` for a single line, `first` telling whether it can start with a BOM
+pub fn normalize_line(line: &str, first: bool) -> &str {
+    let line = if first {
+        line.strip_prefix(BOM).unwrap_or(line)
+    } else {
+        line
+    };
+    line.trim_end()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    #[cfg(feature = "std")]
+    use crate::error::AocError;
+    #[cfg(feature = "std")]
+    use crate::solver::solvers;
+
+    /// `input` as saved by a Windows editor, with extra whitespace at the end
+    #[cfg(feature = "std")]
+    fn windows_style(input: &str) -> String {
+        format!("{}{} \r\n\r\n", BOM, input.replace('\n', "\r\n"))
+    }
+
+    #[test]
+    fn should_normalize() {
+        assert_eq!(normalize("\u{feff}3,4\r\n5 \t\r\n\r\n\n"), "3,4\n5");
+        assert!(matches!(normalize("a\nb\n"), Cow::Borrowed("a\nb")));
+        // Leading whitespace is meaningful for day 4's boards
+        assert_eq!(normalize(" 1  2\r\n 3  4"), " 1  2\n 3  4");
+        assert_eq!(normalize_line("\u{feff}199\r", true), "199");
+        assert_eq!(normalize_line("\u{feff}199", false), "\u{feff}199");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn should_solve_windows_style_examples() -> Result<(), AocError> {
+        for solver in solvers() {
+            for example in solver.examples() {
+                let parsed = solver.parse(&windows_style(example.input))?;
+                if let Some(answer) = example.part1 {
+                    assert_eq!(solver.part1(&parsed)?, answer, "Day {}", solver.day());
+                }
+                if let Some(answer) = example.part2 {
+                    assert_eq!(solver.part2(&parsed)?, answer, "Day {}", solver.day());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn should_stream_windows_style_examples() -> Result<(), AocError> {
+        for day in crate::runner::DAYS {
+            let (streamer, solver) =
+                match (crate::stream::streamer(day), crate::solver::solver(day)) {
+                    (Some(streamer), Some(solver)) => (streamer, solver),
+                    _ => continue,
+                };
+            let example = solver.examples()[0];
+            let [part1, part2] = streamer(&mut windows_style(example.input).as_bytes())?;
+            assert_eq!(
+                part1?.as_str(),
+                example.part1.unwrap_or_default(),
+                "Day {}",
+                day
+            );
+            assert_eq!(
+                part2?.as_str(),
+                example.part2.unwrap_or_default(),
+                "Day {}",
+                day
+            );
+        }
+        Ok(())
+    }
+}
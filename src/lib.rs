@@ -1,24 +1,120 @@
-#![feature(portable_simd)]
-#![feature(int_log)]
+//! Solutions to Advent of Code 2021, usable as a library.
+//!
+//! Each `dayN` module has a `parse` generator turning the puzzle input into the day's domain
+//! types, the algorithms solving it, thin `part1`/`part2` adapters used by `cargo aoc`, and a
+//! `solver()` registered in `solver::solvers()`, along with other implementations of its parts
+//! as `solver::Variant`s. The domain types are re-exported here.
+//!
+//! Without the default `std` feature, the crate is `#![no_std]` and only needs `alloc`: it is
+//! then made of the algorithms of days 6, 10, 14 and 16, `AocError`, `arith` and `geom`, without
+//! the runner, the solvers or the `cargo aoc` glue.
+//!
+//! The `serde` feature makes the parsed inputs of every day serializable, which the `cache`
+//! feature relies on to store them on disk between runs.
+//!
+//! ```
+//! use aoc_2021::{day16, Packet};
+//!
+//! let packet: Packet = "9C0141080250320F1802104A08".parse()?;
+//! assert_eq!(packet.value()?, 1);
+//! assert_eq!(day16::part1(&day16::parse("8A004A801A8002F478")?)?, 16);
+//! # Ok::<(), aoc_2021::AocError>(())
+//! ```
 
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
+#![cfg_attr(feature = "simd", feature(portable_simd))]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
 #[macro_use]
 extern crate aoc_runner_derive;
 
+#[cfg(feature = "std")]
+pub mod answers;
+pub mod arith;
+#[cfg(feature = "cache")]
+pub mod cache;
+#[cfg(feature = "std")]
+pub mod corpus;
+#[cfg(feature = "std")]
 pub mod day1;
 pub mod day10;
+#[cfg(feature = "std")]
 pub mod day11;
+#[cfg(feature = "std")]
 pub mod day12;
+#[cfg(feature = "std")]
 pub mod day13;
 pub mod day14;
+#[cfg(feature = "std")]
 pub mod day15;
 pub mod day16;
+#[cfg(feature = "std")]
 pub mod day2;
+#[cfg(feature = "std")]
 pub mod day3;
+#[cfg(feature = "std")]
 pub mod day4;
+#[cfg(feature = "std")]
 pub mod day5;
 pub mod day6;
+#[cfg(feature = "std")]
 pub mod day7;
+#[cfg(feature = "std")]
 pub mod day8;
+#[cfg(feature = "std")]
 pub mod day9;
+pub mod error;
+#[cfg(feature = "std")]
+pub mod gen;
+pub mod geom;
+#[cfg(feature = "std")]
+pub mod grid;
+pub mod input;
+#[cfg(feature = "std")]
+pub mod lint;
+#[cfg(feature = "std")]
+pub mod memory;
+#[cfg(feature = "std")]
+pub mod oracle;
+#[cfg(feature = "std")]
+pub mod report;
+#[cfg(feature = "std")]
+pub mod runner;
+#[cfg(feature = "std")]
+pub mod solver;
+#[cfg(feature = "std")]
+pub mod stream;
+pub mod trace;
+#[cfg(feature = "std")]
+pub mod viz;
+
+pub use day10::ChunkStatus;
+#[cfg(feature = "std")]
+pub use day11::OctopusMap;
+#[cfg(feature = "std")]
+pub use day12::{Cave, CaveMap};
+#[cfg(feature = "std")]
+pub use day13::{FoldInstruction, Paper};
+pub use day14::Template;
+pub use day16::{OperatorKind, OperatorPacket, Packet, PacketKind};
+#[cfg(feature = "std")]
+pub use day2::Instruction;
+#[cfg(feature = "std")]
+pub use day3::BitCriteria;
+#[cfg(feature = "std")]
+pub use day4::{BingoBoard, BingoInfo};
+#[cfg(feature = "std")]
+pub use day8::Note;
+#[cfg(feature = "std")]
+pub use day9::Heightmap;
+pub use error::{AocError, ParseErrorKind};
+pub use geom::{Bounds, Point2, Segment};
+#[cfg(feature = "std")]
+pub use grid::{Grid, Position};
+#[cfg(feature = "std")]
+pub use solver::{solver, solvers, Solver};
 
+#[cfg(feature = "std")]
 aoc_runner_derive::aoc_lib! { year = 2021 }
@@ -0,0 +1,99 @@
+//! Checks puzzle inputs against each day's format without solving them. Unlike `parse`, which
+//! stops at the first problem, every faulty line is reported, along with problems with the shape
+//! of the whole input such as ragged grids or missing sections.
+
+use crate::error::AocError;
+use crate::input::normalize;
+
+/// Every problem found in an input, with the line and column it is at
+pub type Linter = fn(&str) -> Vec<AocError>;
+
+/// Days whose inputs can be linted
+pub fn linter(day: u8) -> Option<Linter> {
+    match day {
+        1 => Some(crate::day1::lint),
+        2 => Some(crate::day2::lint),
+        3 => Some(crate::day3::lint),
+        4 => Some(crate::day4::lint),
+        5 => Some(crate::day5::lint),
+        6 => Some(crate::day6::lint),
+        7 => Some(crate::day7::lint),
+        8 => Some(crate::day8::lint),
+        9 => Some(crate::day9::lint),
+        10 => Some(crate::day10::lint),
+        11 => Some(crate::day11::lint),
+        12 => Some(crate::day12::lint),
+        13 => Some(crate::day13::lint),
+        14 => Some(crate::day14::lint),
+        15 => Some(crate::day15::lint),
+        16 => Some(crate::day16::lint),
+        _ => None,
+    }
+}
+
+/// Lints `input` once normalized the way the runner does before parsing it
+pub fn lint(day: u8, input: &str) -> Result<Vec<AocError>, AocError> {
+    let linter = linter(day).ok_or(AocError::UnknownDay(day))?;
+    Ok(linter(&normalize(input)))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::error::ParseErrorKind;
+
+    /// Line and column of each problem
+    fn positions(errors: &[AocError]) -> Vec<(usize, usize)> {
+        errors
+            .iter()
+            .map(|e| match e {
+                AocError::Parse { line, column, .. } => (*line, *column),
+                e => panic!("Not a parse error: {}", e),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_accept_examples() -> Result<(), AocError> {
+        for day in crate::runner::DAYS {
+            let solver = crate::solver::solver(day).ok_or(AocError::UnknownDay(day))?;
+            for example in solver.examples() {
+                assert_eq!(lint(day, example.input)?, vec![], "Day {}", day);
+            }
+        }
+        assert_eq!(lint(26, "").err(), Some(AocError::UnknownDay(26)));
+        Ok(())
+    }
+
+    #[test]
+    fn should_report_every_problem() -> Result<(), AocError> {
+        assert_eq!(
+            positions(&lint(1, "199\n2x0\n\n201\r\n-3")?),
+            [(2, 1), (3, 1), (5, 1)]
+        );
+        assert_eq!(
+            positions(&lint(3, "00100\n1111\n10110\n101101")?),
+            [(2, 5), (4, 6)]
+        );
+        assert_eq!(positions(&lint(9, "2199\n39x7\n985")?), [(2, 3), (3, 4)]);
+        assert_eq!(
+            positions(&lint(13, "6,10\n0,x\n\nfold along z=3")?),
+            [(2, 3), (4, 12)]
+        );
+        assert_eq!(positions(&lint(16, "8A0G\nC2")?), [(1, 4), (2, 1)]);
+
+        let bingo = include_str!("../examples/day4/example.txt")
+            .replacen("7,4,9", "7,x,9", 1)
+            .replacen("21  9 14", "21  9 1y", 1)
+            .replacen("\n 2  0 12  3  7", "", 1);
+        assert_eq!(positions(&lint(4, &bingo)?), [(1, 3), (5, 7), (19, 1)]);
+        assert!(matches!(
+            lint(16, "")?.as_slice(),
+            [AocError::Parse {
+                kind: ParseErrorKind::Expected(_),
+                ..
+            }]
+        ));
+        Ok(())
+    }
+}
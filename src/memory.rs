@@ -0,0 +1,175 @@
+//! Allocation counting for profiling the memory behavior of the solvers. The `count-allocs`
+//! feature installs a global allocator keeping counts per thread, so that days running
+//! concurrently don't add up. Without it, nothing is counted.
+
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct AllocStats {
+    /// Number of allocations, reallocations included
+    pub allocations: u64,
+    /// Bytes allocated, a reallocation counting for its new size
+    pub bytes: u64,
+    /// Most bytes live at once, on top of what was live when counting started
+    pub peak_bytes: u64,
+}
+
+impl fmt::Display for AllocStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} allocs, {} B, peak {} B",
+            self.allocations, self.bytes, self.peak_bytes
+        )
+    }
+}
+
+/// Runs `f`, along with the allocations it made on this thread if they are counted
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Option<AllocStats>) {
+    #[cfg(feature = "count-allocs")]
+    {
+        let (result, stats) = counting::measure(f);
+        (result, Some(stats))
+    }
+    #[cfg(not(feature = "count-allocs"))]
+    (f(), None)
+}
+
+#[cfg(feature = "count-allocs")]
+mod counting {
+    use super::AllocStats;
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    #[derive(Clone, Copy)]
+    struct Counters {
+        allocations: u64,
+        bytes: u64,
+        // Memory can be freed by another thread than the one allocating it
+        live: i64,
+        peak: i64,
+    }
+
+    thread_local! {
+        // Const and without destructor, so it never allocates itself
+        static COUNTERS: Cell<Counters> = const {
+            Cell::new(Counters {
+                allocations: 0,
+                bytes: 0,
+                live: 0,
+                peak: 0,
+            })
+        };
+    }
+
+    fn update<T>(f: impl FnOnce(&mut Counters) -> T) -> Option<T> {
+        COUNTERS
+            .try_with(|counters| {
+                let mut updated = counters.get();
+                let result = f(&mut updated);
+                counters.set(updated);
+                result
+            })
+            .ok()
+    }
+
+    fn record(allocated: usize, freed: usize) {
+        update(|c| {
+            if allocated > 0 {
+                c.allocations += 1;
+                c.bytes += allocated as u64;
+            }
+            c.live += allocated as i64 - freed as i64;
+            c.peak = c.peak.max(c.live);
+        });
+    }
+
+    pub fn measure<T>(f: impl FnOnce() -> T) -> (T, AllocStats) {
+        let before = update(|c| {
+            let before = *c;
+            c.peak = c.live;
+            before
+        })
+        .expect("Counters are available while the thread runs");
+        let result = f();
+        let stats = update(|c| {
+            let stats = AllocStats {
+                allocations: c.allocations - before.allocations,
+                bytes: c.bytes - before.bytes,
+                peak_bytes: (c.peak - before.live).max(0) as u64,
+            };
+            // Nested measures don't hide the peak from the outer one
+            c.peak = c.peak.max(before.peak);
+            stats
+        })
+        .expect("Counters are available while the thread runs");
+        (result, stats)
+    }
+
+    struct CountingAllocator;
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc(layout);
+            if !ptr.is_null() {
+                record(layout.size(), 0);
+            }
+            ptr
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc_zeroed(layout);
+            if !ptr.is_null() {
+                record(layout.size(), 0);
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout);
+            record(0, layout.size());
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_ptr = System.realloc(ptr, layout, new_size);
+            if !new_ptr.is_null() {
+                record(new_size, layout.size());
+            }
+            new_ptr
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: CountingAllocator = CountingAllocator;
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "count-allocs")]
+    fn should_count_allocations() {
+        let (_, stats) = measure(|| {
+            let mut v: Vec<u8> = Vec::with_capacity(100);
+            v.extend(std::iter::repeat_n(0, 1000));
+            drop(v);
+            let (_, inner) = measure(|| Box::new([0u8; 10]));
+            inner
+        });
+        let stats = stats.expect("Allocations are counted");
+        assert_eq!(stats.allocations, 3);
+        assert!(stats.bytes >= 1110);
+        assert!(stats.peak_bytes >= 1000 && stats.peak_bytes < 1110);
+
+        let (inner, _) = measure(|| measure(|| Box::new(1)).1);
+        assert_eq!(inner.map(|s| s.allocations), Some(1));
+    }
+
+    #[test]
+    #[cfg(not(feature = "count-allocs"))]
+    fn should_not_count_allocations() {
+        assert_eq!(measure(|| vec![1, 2, 3]).1, None);
+    }
+}
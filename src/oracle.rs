@@ -0,0 +1,1263 @@
+//! Brute-force reference solutions, written straight from the puzzle text without any of the
+//! tricks of the `dayN` modules. They take the same parsed input and give the same answers, only
+//! much slower, and are checked against the real solvers on random inputs by property tests.
+//!
+//! Oracles are only meant for valid inputs, and some parts are out of their reach: day 6 and
+//! day 14 only have a part 1, their part 2 needing more memory than there is.
+
+use crate::error::AocError;
+
+/// Name of the oracles among the variants of each day, see `solver::Variant`
+pub const VARIANT: &str = "naive";
+
+pub mod day1 {
+    /// Number of times a sum of `window` depths is larger than the previous one
+    fn increases(depths: &[u32], window: usize) -> usize {
+        let sums: Vec<u32> = depths.windows(window).map(|w| w.iter().sum()).collect();
+        sums.windows(2).filter(|pair| pair[0] < pair[1]).count()
+    }
+
+    pub fn part1(depths: &[u32]) -> usize {
+        increases(depths, 1)
+    }
+
+    pub fn part2(depths: &[u32]) -> usize {
+        increases(depths, 3)
+    }
+}
+
+pub mod day2 {
+    use crate::day2::Instruction;
+
+    fn forward(i: &Instruction) -> i32 {
+        match i {
+            Instruction::Forward(x) => *x,
+            _ => 0,
+        }
+    }
+
+    fn down(i: &Instruction) -> i32 {
+        match i {
+            Instruction::Down(x) => *x,
+            Instruction::Up(x) => -x,
+            Instruction::Forward(_) => 0,
+        }
+    }
+
+    pub fn part1(instructions: &[Instruction]) -> i32 {
+        let x: i32 = instructions.iter().map(forward).sum();
+        let depth: i32 = instructions.iter().map(down).sum();
+        x * depth
+    }
+
+    /// The aim at each `forward` is summed again from the start
+    pub fn part2(instructions: &[Instruction]) -> i32 {
+        let x: i32 = instructions.iter().map(forward).sum();
+        let depth = (0..instructions.len())
+            .map(|i| forward(&instructions[i]) * instructions[..i].iter().map(down).sum::<i32>())
+            .sum::<i32>();
+        x * depth
+    }
+}
+
+pub mod day3 {
+    use super::AocError;
+
+    /// The numbers in binary, as wide as the largest one
+    fn binary(input: &[u16]) -> Vec<String> {
+        let max = input.iter().copied().max().unwrap_or(0);
+        let width = (u16::BITS - max.leading_zeros()) as usize;
+        input
+            .iter()
+            .map(|x| format!("{:0width$b}", x, width = width))
+            .map(|x| x[x.len() - width..].to_string())
+            .collect()
+    }
+
+    fn ones(numbers: &[String], column: usize) -> usize {
+        numbers
+            .iter()
+            .filter(|x| x.as_bytes()[column] == b'1')
+            .count()
+    }
+
+    fn to_number(bits: &str) -> u32 {
+        u32::from_str_radix(bits, 2).unwrap_or(0)
+    }
+
+    pub fn part1(input: &[u16]) -> u32 {
+        let numbers = binary(input);
+        let width = numbers.first().map_or(0, String::len);
+        let (mut gamma, mut epsilon) = (String::new(), String::new());
+        for column in 0..width {
+            let ones = ones(&numbers, column);
+            let most_common_is_one = ones >= numbers.len() - ones;
+            gamma.push(if most_common_is_one { '1' } else { '0' });
+            epsilon.push(if most_common_is_one { '0' } else { '1' });
+        }
+        to_number(&gamma) * to_number(&epsilon)
+    }
+
+    fn rating(input: &[u16], most_common: bool) -> Result<u32, AocError> {
+        let mut numbers = binary(input);
+        let width = numbers.first().map_or(0, String::len);
+        for column in 0..width {
+            if numbers.len() == 1 {
+                break;
+            }
+            let ones = ones(&numbers, column);
+            let zeros = numbers.len() - ones;
+            let keep = match most_common {
+                true if ones >= zeros => b'1',
+                true => b'0',
+                false if zeros <= ones => b'0',
+                false => b'1',
+            };
+            numbers.retain(|x| x.as_bytes()[column] == keep);
+        }
+        match numbers.as_slice() {
+            [number] => Ok(to_number(number)),
+            _ => Err(AocError::no_solution(3, "no single number is left")),
+        }
+    }
+
+    pub fn part2(input: &[u16]) -> Result<u32, AocError> {
+        Ok(rating(input, true)? * rating(input, false)?)
+    }
+}
+
+pub mod day4 {
+    use super::AocError;
+    use crate::arith;
+    use crate::day4::{BingoBoard, BingoInfo};
+
+    /// Number of draws after which the board has a full row or column, and its score then
+    fn win(board: &BingoBoard, numbers: &[u32]) -> Option<(usize, Result<u32, AocError>)> {
+        let width = board.rows.iter().map(Vec::len).max().unwrap_or(0);
+        (1..=numbers.len()).find_map(|drawn| {
+            let marked = |x: &Option<u32>| x.is_some_and(|x| numbers[..drawn].contains(&x));
+            let full_row = board.rows.iter().any(|r| r.iter().all(marked));
+            let full_column =
+                (0..width).any(|c| board.rows.iter().all(|r| r.get(c).is_none_or(marked)));
+            (full_row || full_column).then(|| {
+                let unmarked = board.rows.iter().flatten().filter(|x| !marked(x)).flatten();
+                let score = arith::sum(4, unmarked.copied())
+                    .and_then(|unmarked| arith::mul(4, unmarked, numbers[drawn - 1]));
+                (drawn, score)
+            })
+        })
+    }
+
+    /// Boards that win, along with their index to break ties
+    fn wins(input: &BingoInfo) -> Result<Vec<(usize, usize, u32)>, AocError> {
+        input
+            .boards
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, b)| win(b, &input.numbers).map(|(drawn, score)| (drawn, idx, score)))
+            .map(|(drawn, idx, score)| Ok((drawn, idx, score?)))
+            .collect()
+    }
+
+    pub fn part1(input: &BingoInfo) -> Result<u32, AocError> {
+        wins(input)?
+            .into_iter()
+            .min()
+            .map(|(_, _, score)| score)
+            .ok_or_else(|| AocError::no_solution(4, "no board wins"))
+    }
+
+    pub fn part2(input: &BingoInfo) -> Result<u32, AocError> {
+        let wins = wins(input)?;
+        match wins.iter().max() {
+            Some(&(_, _, score)) if wins.len() == input.boards.len() => Ok(score),
+            _ => Err(AocError::no_solution(4, "not every board wins")),
+        }
+    }
+}
+
+pub mod day5 {
+    use crate::geom::{Bounds, Point2, Segment};
+
+    /// Whether `p` is on the segment, whatever its angle
+    fn covers(segment: &Segment<i32>, p: Point2<i32>) -> bool {
+        let (d, e) = (segment.end - segment.start, p - segment.start);
+        d.x * e.y == d.y * e.x && segment.bounds().contains(p)
+    }
+
+    /// Points of the map covered by at least two segments, every point being checked
+    fn overlaps<'a>(segments: impl Iterator<Item = &'a Segment<i32>> + Clone) -> usize {
+        let ends = segments.clone().flat_map(|s| [s.start, s.end]);
+        let bounds = match Bounds::of(ends) {
+            Some(bounds) => bounds,
+            None => return 0,
+        };
+        (bounds.min.y..=bounds.max.y)
+            .flat_map(|y| (bounds.min.x..=bounds.max.x).map(move |x| Point2::new(x, y)))
+            .filter(|&p| segments.clone().filter(|s| covers(s, p)).count() >= 2)
+            .count()
+    }
+
+    pub fn part1(input: &[Segment<i32>]) -> usize {
+        overlaps(
+            input
+                .iter()
+                .filter(|s| s.is_horizontal() || s.is_vertical()),
+        )
+    }
+
+    pub fn part2(input: &[Segment<i32>]) -> usize {
+        overlaps(input.iter())
+    }
+}
+
+pub mod day6 {
+    /// Number of lanternfish after `days`, simulated one by one
+    pub fn fishes_for_day(input: &[usize], days: usize) -> usize {
+        let mut fishes = input.to_vec();
+        for _ in 0..days {
+            let mut newborns = 0;
+            for timer in fishes.iter_mut() {
+                if *timer == 0 {
+                    *timer = 6;
+                    newborns += 1;
+                } else {
+                    *timer -= 1;
+                }
+            }
+            fishes.extend(std::iter::repeat_n(8, newborns));
+        }
+        fishes.len()
+    }
+
+    pub fn part1(input: &[usize]) -> usize {
+        fishes_for_day(input, 80)
+    }
+}
+
+pub mod day7 {
+    /// Every position between the leftmost and the rightmost crabs is tried
+    fn cheapest_alignment(input: &[i32], cost: impl Fn(i32) -> i32) -> i32 {
+        let (min, max) = (input.iter().min(), input.iter().max());
+        match (min, max) {
+            (Some(&min), Some(&max)) => (min..=max)
+                .map(|target| input.iter().map(|x| cost((target - x).abs())).sum())
+                .min()
+                .unwrap_or(0),
+            _ => 0,
+        }
+    }
+
+    pub fn part1(input: &[i32]) -> i32 {
+        cheapest_alignment(input, |distance| distance)
+    }
+
+    pub fn part2(input: &[i32]) -> i32 {
+        cheapest_alignment(input, |distance| distance * (distance + 1) / 2)
+    }
+}
+
+pub mod day8 {
+    use super::AocError;
+    use crate::day8::Note;
+
+    /// Segments lit by each digit
+    pub const DIGITS: [&str; 10] = [
+        "abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg",
+    ];
+
+    /// Every way to connect the wires `a` to `g` to the segments
+    fn wirings(prefix: &mut Vec<char>, wirings: &mut Vec<Vec<char>>) {
+        if prefix.len() == 7 {
+            wirings.push(prefix.clone());
+            return;
+        }
+        for segment in "abcdefg".chars() {
+            if !prefix.contains(&segment) {
+                prefix.push(segment);
+                self::wirings(prefix, wirings);
+                prefix.pop();
+            }
+        }
+    }
+
+    /// Digit shown by a pattern once its wires are connected to segments by `wiring`
+    fn decode(pattern: &str, wiring: &[char]) -> Option<u32> {
+        let mut segments: Vec<char> = pattern
+            .chars()
+            .map(|c| wiring[(c as u8 - b'a') as usize])
+            .collect();
+        segments.sort_unstable();
+        let segments: String = segments.into_iter().collect();
+        DIGITS.iter().position(|d| *d == segments).map(|d| d as u32)
+    }
+
+    /// Digits of the outputs, with the first wiring under which every pattern is a digit
+    fn outputs(note: &Note, wirings: &[Vec<char>]) -> Option<Vec<u32>> {
+        let wiring = wirings
+            .iter()
+            .find(|w| note.inputs.iter().all(|i| decode(i, w).is_some()))?;
+        note.outputs.iter().map(|o| decode(o, wiring)).collect()
+    }
+
+    fn all_wirings() -> Vec<Vec<char>> {
+        let mut all = Vec::new();
+        wirings(&mut Vec::new(), &mut all);
+        all
+    }
+
+    /// Notes that can't be untangled count for nothing
+    pub fn part1(input: &[Note]) -> usize {
+        let wirings = all_wirings();
+        input
+            .iter()
+            .filter_map(|note| outputs(note, &wirings))
+            .flatten()
+            .filter(|digit| matches!(digit, 1 | 4 | 7 | 8))
+            .count()
+    }
+
+    pub fn part2(input: &[Note]) -> Result<u32, AocError> {
+        let wirings = all_wirings();
+        input
+            .iter()
+            .map(|note| {
+                let digits = outputs(note, &wirings)
+                    .ok_or_else(|| AocError::no_solution(8, "no wiring matches the patterns"))?;
+                Ok(digits.iter().fold(0, |value, digit| value * 10 + digit))
+            })
+            .sum()
+    }
+}
+
+pub mod day9 {
+    use crate::day9::Heightmap;
+    use crate::grid::Grid;
+    use std::collections::HashMap;
+
+    pub fn part1(input: &Heightmap) -> u32 {
+        input
+            .positions()
+            .filter(|&p| input.orthogonal_neighbors(p).all(|n| input[n] > input[p]))
+            .map(|p| input[p] as u32 + 1)
+            .sum()
+    }
+
+    /// Every cell below 9 starts in its own basin, then joins the smallest basin next to it
+    /// until none changes
+    pub fn part2(input: &Heightmap) -> usize {
+        let mut basins: Grid<Option<usize>> = Grid::from_fn(input.width(), input.height(), |p| {
+            (input[p] != 9).then_some(p.y * input.width() + p.x)
+        });
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for p in input.positions() {
+                let smallest = input
+                    .orthogonal_neighbors(p)
+                    .filter_map(|n| basins[n])
+                    .min();
+                match (basins[p], smallest) {
+                    (Some(basin), Some(smallest)) if smallest < basin => {
+                        basins[p] = Some(smallest);
+                        changed = true;
+                    }
+                    _ => (),
+                }
+            }
+        }
+        let mut sizes: HashMap<usize, usize> = HashMap::new();
+        basins
+            .iter()
+            .flatten()
+            .for_each(|&b| *sizes.entry(b).or_default() += 1);
+        let mut sizes: Vec<usize> = sizes.into_values().collect();
+        sizes.sort_unstable_by(|a, b| b.cmp(a));
+        sizes.iter().take(3).product()
+    }
+}
+
+pub mod day10 {
+    use super::AocError;
+
+    /// What is left of a line once matching pairs are removed, over and over
+    fn reduce(line: &str) -> String {
+        let mut line = line.to_string();
+        loop {
+            let reduced = ["()", "[]", "{}", "<>"]
+                .iter()
+                .fold(line.clone(), |l, pair| l.replace(pair, ""));
+            if reduced.len() == line.len() {
+                return line;
+            }
+            line = reduced;
+        }
+    }
+
+    pub fn part1(input: &[String]) -> u32 {
+        input
+            .iter()
+            .filter_map(|l| reduce(l).chars().find(|c| ")]}>".contains(*c)))
+            .map(|c| match c {
+                ')' => 3,
+                ']' => 57,
+                '}' => 1197,
+                _ => 25137,
+            })
+            .sum()
+    }
+
+    pub fn part2(input: &[String]) -> Result<u64, AocError> {
+        let mut scores: Vec<u64> = input
+            .iter()
+            .map(|l| reduce(l))
+            .filter(|l| !l.is_empty() && !l.contains([')', ']', '}', '>']))
+            .map(|l| {
+                l.chars().rev().try_fold(0u64, |total, c| {
+                    let score = "([{<".find(c).map_or(0, |idx| idx as u64 + 1);
+                    total
+                        .checked_mul(5)
+                        .and_then(|total| total.checked_add(score))
+                        .ok_or(AocError::Overflow(10))
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        scores.sort_unstable();
+        scores
+            .get(scores.len() / 2)
+            .copied()
+            .ok_or_else(|| AocError::no_solution(10, "no line is incomplete"))
+    }
+}
+
+pub mod day11 {
+    use super::AocError;
+    use crate::grid::Grid;
+
+    /// Number of octopuses that flashed, the whole map being scanned again after each flash
+    fn step(levels: &mut [Vec<u8>]) -> usize {
+        let (height, width) = (levels.len(), levels[0].len());
+        levels.iter_mut().flatten().for_each(|o| *o += 1);
+        let mut flashed = vec![vec![false; width]; height];
+        'scan: loop {
+            for y in 0..height {
+                for x in 0..width {
+                    if levels[y][x] > 9 && !flashed[y][x] {
+                        flashed[y][x] = true;
+                        let (top, left) = (y.saturating_sub(1), x.saturating_sub(1));
+                        for row in &mut levels[top..=(y + 1).min(height - 1)] {
+                            for o in &mut row[left..=(x + 1).min(width - 1)] {
+                                *o += 1;
+                            }
+                        }
+                        continue 'scan;
+                    }
+                }
+            }
+            break;
+        }
+        levels.iter_mut().flatten().for_each(|o| {
+            if *o > 9 {
+                *o = 0
+            }
+        });
+        flashed.iter().flatten().filter(|&&f| f).count()
+    }
+
+    fn levels(input: &Grid<u8>) -> Vec<Vec<u8>> {
+        input.rows().map(<[u8]>::to_vec).collect()
+    }
+
+    pub fn part1(input: &Grid<u8>) -> usize {
+        let mut levels = levels(input);
+        (0..100).map(|_| step(&mut levels)).sum()
+    }
+
+    /// Every state is kept to notice when the map loops without synchronizing
+    pub fn part2(input: &Grid<u8>) -> Result<usize, AocError> {
+        let mut levels = levels(input);
+        let mut seen = vec![levels.clone()];
+        for steps in 1.. {
+            if step(&mut levels) == input.len() {
+                return Ok(steps);
+            }
+            if seen.contains(&levels) {
+                break;
+            }
+            seen.push(levels.clone());
+        }
+        Err(AocError::no_solution(
+            11,
+            "octopuses never flash all at once",
+        ))
+    }
+}
+
+pub mod day12 {
+    use crate::day12::{Cave, CaveMap};
+
+    /// Every path is kept whole, and checked again for each cave added to it
+    fn count_paths(input: &CaveMap, visit_twice: bool) -> usize {
+        let mut paths = vec![vec![Cave::Small("start".to_string())]];
+        let mut complete = 0;
+        while let Some(path) = paths.pop() {
+            let head = &path[path.len() - 1];
+            if head.name() == "end" {
+                complete += 1;
+                continue;
+            }
+            let visits = |cave: &Cave| path.iter().filter(|c| *c == cave).count();
+            let small_twice = path
+                .iter()
+                .any(|c| matches!(c, Cave::Small(_)) && visits(c) > 1);
+            for next in input.neighbors(head) {
+                let allowed = match next {
+                    Cave::Big(_) => true,
+                    Cave::Small(name) => {
+                        visits(next) == 0 || (visit_twice && !small_twice && name != "start")
+                    }
+                };
+                if allowed {
+                    let mut path = path.clone();
+                    path.push(next.clone());
+                    paths.push(path);
+                }
+            }
+        }
+        complete
+    }
+
+    pub fn part1(input: &CaveMap) -> usize {
+        count_paths(input, false)
+    }
+
+    pub fn part2(input: &CaveMap) -> usize {
+        count_paths(input, true)
+    }
+}
+
+pub mod day13 {
+    use super::AocError;
+    use crate::day13::{FoldInstruction, Paper};
+
+    /// The whole sheet, large enough for every dot and fold line
+    fn sheet(input: &Paper) -> Vec<Vec<bool>> {
+        let mut width = input.points.iter().map(|p| p.x + 1).max().unwrap_or(1);
+        let mut height = input.points.iter().map(|p| p.y + 1).max().unwrap_or(1);
+        for fold in &input.instructions {
+            match fold {
+                FoldInstruction::X(i) => width = width.max(2 * i + 1),
+                FoldInstruction::Y(i) => height = height.max(2 * i + 1),
+            }
+        }
+        let mut sheet = vec![vec![false; width as usize]; height as usize];
+        input
+            .points
+            .iter()
+            .for_each(|p| sheet[p.y as usize][p.x as usize] = true);
+        sheet
+    }
+
+    /// The half of the sheet before the fold line, with the dots of the other half on top
+    fn fold(sheet: &[Vec<bool>], fold: &FoldInstruction) -> Vec<Vec<bool>> {
+        let cell = |x: usize, y: usize| sheet.get(y).and_then(|r| r.get(x)).copied();
+        match *fold {
+            FoldInstruction::X(i) => {
+                let i = i as usize;
+                (0..sheet.len())
+                    .map(|y| {
+                        (0..i)
+                            .map(|x| cell(x, y) == Some(true) || cell(2 * i - x, y) == Some(true))
+                            .collect()
+                    })
+                    .collect()
+            }
+            FoldInstruction::Y(i) => {
+                let i = i as usize;
+                let width = sheet.first().map_or(0, Vec::len);
+                (0..i)
+                    .map(|y| {
+                        (0..width)
+                            .map(|x| cell(x, y) == Some(true) || cell(x, 2 * i - y) == Some(true))
+                            .collect()
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    pub fn part1(input: &Paper) -> Result<usize, AocError> {
+        let first = input
+            .instructions
+            .first()
+            .ok_or_else(|| AocError::no_solution(13, "there is no fold instruction"))?;
+        Ok(fold(&sheet(input), first)
+            .iter()
+            .flatten()
+            .filter(|&&dot| dot)
+            .count())
+    }
+
+    /// The folded sheet, cut after its last dots
+    pub fn part2(input: &Paper) -> String {
+        let sheet = input
+            .instructions
+            .iter()
+            .fold(sheet(input), |sheet, f| fold(&sheet, f));
+        let dots = || {
+            sheet
+                .iter()
+                .enumerate()
+                .flat_map(|(y, r)| r.iter().enumerate().map(move |(x, &dot)| (x, y, dot)))
+                .filter(|&(_, _, dot)| dot)
+        };
+        let max_x = dots().map(|(x, _, _)| x).max().unwrap_or(0);
+        let max_y = dots().map(|(_, y, _)| y).max().unwrap_or(0);
+        let mut output = String::new();
+        for y in 0..=max_y {
+            output += "\n";
+            for x in 0..=max_x {
+                let dot = sheet.get(y).and_then(|r| r.get(x)) == Some(&true);
+                output += if dot { "█" } else { " " };
+            }
+        }
+        output
+    }
+}
+
+pub mod day14 {
+    use super::AocError;
+    use crate::day14::Template;
+    use std::collections::BTreeMap;
+
+    /// Difference between the most and least common elements of the polymer, built whole
+    pub fn score_after(input: &Template, steps: usize) -> Result<usize, AocError> {
+        let mut polymer: Vec<char> = input.init.chars().collect();
+        for _ in 0..steps {
+            let mut next: Vec<char> = polymer.iter().take(1).copied().collect();
+            for pair in polymer.windows(2) {
+                let inserted = input
+                    .templates
+                    .get(&(pair[0], pair[1]))
+                    .ok_or_else(|| AocError::no_solution(14, "a pair has no insertion rule"))?;
+                next.extend([*inserted, pair[1]]);
+            }
+            polymer = next;
+        }
+        let mut counts: BTreeMap<char, usize> = BTreeMap::new();
+        polymer
+            .iter()
+            .for_each(|&c| *counts.entry(c).or_default() += 1);
+        Ok(counts.values().max().unwrap_or(&0) - counts.values().min().unwrap_or(&0))
+    }
+
+    pub fn part1(input: &Template) -> Result<usize, AocError> {
+        score_after(input, 10)
+    }
+}
+
+pub mod day15 {
+    use crate::geom::Point2;
+    use crate::grid::Grid;
+
+    /// Lowest total risk to reach each cell, relaxed from its neighbors until none improves
+    /// (Bellman-Ford)
+    fn lowest_risk(chitons: &Grid<u8>) -> u32 {
+        let mut risks = chitons.map(|_| u32::MAX);
+        risks[Point2::new(0, 0)] = 0;
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for p in chitons.positions() {
+                for n in chitons.orthogonal_neighbors(p) {
+                    if risks[n] != u32::MAX && risks[n] + (chitons[p] as u32) < risks[p] {
+                        risks[p] = risks[n] + chitons[p] as u32;
+                        changed = true;
+                    }
+                }
+            }
+        }
+        risks[Point2::new(chitons.width() - 1, chitons.height() - 1)]
+    }
+
+    pub fn part1(input: &Grid<u8>) -> u32 {
+        lowest_risk(input)
+    }
+
+    /// The risk of each tile goes up one tile at a time
+    pub fn part2(input: &Grid<u8>) -> u32 {
+        let (width, height) = (input.width(), input.height());
+        let cave = Grid::from_fn(width * 5, height * 5, |p| {
+            let mut risk = input[Point2::new(p.x % width, p.y % height)];
+            for _ in 0..p.x / width + p.y / height {
+                risk = if risk == 9 { 1 } else { risk + 1 };
+            }
+            risk
+        });
+        lowest_risk(&cave)
+    }
+}
+
+pub mod day16 {
+    use super::AocError;
+    use std::str::Chars;
+
+    /// The transmission, read one bit at a time
+    struct Bits<'a> {
+        bits: Chars<'a>,
+        read: usize,
+    }
+
+    impl Bits<'_> {
+        fn take(&mut self, count: usize) -> Result<u64, AocError> {
+            let mut value = 0;
+            for _ in 0..count {
+                let bit = match self.bits.next() {
+                    Some('0') => 0,
+                    Some('1') => 1,
+                    _ => return Err(AocError::no_solution(16, "the packet is cut short")),
+                };
+                value = value << 1 | bit;
+                self.read += 1;
+            }
+            Ok(value)
+        }
+    }
+
+    /// Version sum and value of the next packet, evaluated while it is read without building
+    /// it. The value is `None` when it overflows.
+    fn packet(bits: &mut Bits) -> Result<(u64, Option<u64>), AocError> {
+        let version = bits.take(3)?;
+        let type_id = bits.take(3)?;
+        if type_id == 4 {
+            let mut value = Some(0u64);
+            loop {
+                let more = bits.take(1)?;
+                let group = bits.take(4)?;
+                value = value.and_then(|v| v.checked_mul(16)).map(|v| v + group);
+                if more == 0 {
+                    return Ok((version, value));
+                }
+            }
+        }
+
+        let mut versions = version;
+        let mut values = Vec::new();
+        if bits.take(1)? == 0 {
+            let length = bits.take(15)? as usize;
+            let end = bits.read + length;
+            while bits.read < end {
+                let (v, value) = packet(bits)?;
+                versions += v;
+                values.push(value);
+            }
+        } else {
+            for _ in 0..bits.take(11)? {
+                let (v, value) = packet(bits)?;
+                versions += v;
+                values.push(value);
+            }
+        }
+        let values: Option<Vec<u64>> = values.into_iter().collect();
+        let value = values.and_then(|values| {
+            let compare = |f: fn(u64, u64) -> bool| match values[..] {
+                [a, b] => Some(f(a, b) as u64),
+                _ => None,
+            };
+            match type_id {
+                0 => values.iter().try_fold(0u64, |a, &b| a.checked_add(b)),
+                1 => values.iter().try_fold(1u64, |a, &b| a.checked_mul(b)),
+                2 => values.iter().min().copied(),
+                3 => values.iter().max().copied(),
+                5 => compare(|a, b| a > b),
+                6 => compare(|a, b| a < b),
+                _ => compare(|a, b| a == b),
+            }
+        });
+        Ok((versions, value))
+    }
+
+    fn read(input: &str) -> Result<(u64, Option<u64>), AocError> {
+        packet(&mut Bits {
+            bits: input.chars(),
+            read: 0,
+        })
+    }
+
+    pub fn part1(input: &str) -> Result<u64, AocError> {
+        Ok(read(input)?.0)
+    }
+
+    pub fn part2(input: &str) -> Result<u64, AocError> {
+        read(input)?
+            .1
+            .ok_or_else(|| AocError::no_solution(16, "the value is out of range"))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::day13::{fold_points, FoldInstruction};
+    use crate::day16::{OperatorKind, OperatorPacket, Packet, PacketKind};
+    use crate::geom::Point2;
+    use crate::{
+        day1, day10, day11, day12, day13, day14, day15, day16, day2, day3, day4, day5, day6, day7,
+        day8, day9,
+    };
+    use proptest::collection::{btree_set, vec};
+    use proptest::prelude::*;
+
+    /// Cases per property, raised with `PROPTEST_CASES` for longer runs
+    fn config(cases: u32) -> ProptestConfig {
+        ProptestConfig {
+            cases: std::env::var("PROPTEST_CASES")
+                .ok()
+                .and_then(|c| c.parse().ok())
+                .unwrap_or(cases),
+            ..ProptestConfig::default()
+        }
+    }
+
+    fn lines<T: ToString>(items: impl IntoIterator<Item = T>, separator: &str) -> String {
+        let items: Vec<String> = items.into_iter().map(|x| x.to_string()).collect();
+        items.join(separator)
+    }
+
+    /// Rows of `width` digits taken from `digit`
+    fn digits(
+        width: impl Strategy<Value = usize>,
+        height: impl Strategy<Value = usize>,
+        digit: impl Strategy<Value = u8> + Clone,
+    ) -> impl Strategy<Value = String> {
+        (width, height).prop_flat_map(move |(width, height)| {
+            vec(digit.clone(), width * height)
+                .prop_map(move |cells| lines(cells.chunks(width).map(|r| lines(r, "")), "\n"))
+        })
+    }
+
+    fn day1_input() -> impl Strategy<Value = String> {
+        vec(0u32..10_000, 0..100).prop_map(|depths| lines(depths, "\n"))
+    }
+
+    fn day2_input() -> impl Strategy<Value = String> {
+        let instruction = (prop::sample::select(&["forward", "down", "up"][..]), 1..10);
+        vec(instruction, 0..50).prop_map(|instructions| {
+            lines(
+                instructions.iter().map(|(d, x)| format!("{} {}", d, x)),
+                "\n",
+            )
+        })
+    }
+
+    /// Distinct numbers of `width` bits
+    fn day3_input() -> impl Strategy<Value = String> {
+        (1usize..=12).prop_flat_map(|width| {
+            let count = 1..=(1 << width).min(40);
+            btree_set(0u16..(1 << width), count)
+                .prop_map(Vec::from_iter)
+                .prop_shuffle()
+                .prop_map(move |numbers| {
+                    let numbers = numbers.iter().map(|x| format!("{:0w$b}", x, w = width));
+                    lines(numbers, "\n")
+                })
+        })
+    }
+
+    /// Distinct numbers drawn, some boards not winning when few are
+    fn day4_input() -> impl Strategy<Value = String> {
+        let shuffled = || Just((0..40u32).collect::<Vec<_>>()).prop_shuffle();
+        (shuffled(), 1usize..=40, vec(shuffled(), 1..5)).prop_map(|(draws, count, boards)| {
+            let mut input = lines(&draws[..count], ",");
+            for board in boards {
+                input += "\n";
+                for row in board[..25].chunks(5) {
+                    input += "\n";
+                    input += &lines(row.iter().map(|x| format!("{:2}", x)), " ");
+                }
+            }
+            input
+        })
+    }
+
+    /// Horizontal, vertical or diagonal segments
+    fn day5_input() -> impl Strategy<Value = String> {
+        let segment = (0i32..20, 0i32..20, 0i32..20, 0i32..20, 0..3, any::<bool>());
+        vec(segment, 1..15).prop_map(|segments| {
+            let segments = segments.into_iter().map(|(x1, y1, x2, y2, kind, up)| {
+                let (x2, y2) = match kind {
+                    0 => (x2, y1),
+                    1 => (x1, y2),
+                    _ if up && y1 >= (x2 - x1).abs() => (x2, y1 - (x2 - x1).abs()),
+                    _ => (x2, y1 + (x2 - x1).abs()),
+                };
+                format!("{},{} -> {},{}", x1, y1, x2, y2)
+            });
+            lines(segments, "\n")
+        })
+    }
+
+    fn day6_input() -> impl Strategy<Value = String> {
+        vec(0usize..=8, 1..10).prop_map(|timers| lines(timers, ","))
+    }
+
+    fn day7_input() -> impl Strategy<Value = String> {
+        vec(-1000i32..1000, 1..100).prop_map(|crabs| lines(crabs, ","))
+    }
+
+    /// Wires connected at random, patterns in any order and their segments too
+    fn day8_note() -> impl Strategy<Value = String> {
+        let wiring = Just("abcdefg".chars().collect::<Vec<_>>()).prop_shuffle();
+        let order = Just((0..10).collect::<Vec<usize>>()).prop_shuffle();
+        let keys = prop::array::uniform7(any::<u8>());
+        (wiring, order, vec(0usize..10, 4), keys).prop_map(|(wiring, order, outputs, keys)| {
+            let pattern = |digit: usize| {
+                let mut wires: Vec<char> = super::day8::DIGITS[digit]
+                    .chars()
+                    .map(|s| wiring[(s as u8 - b'a') as usize])
+                    .collect();
+                wires.sort_by_key(|&w| keys[(w as u8 - b'a') as usize]);
+                wires.into_iter().collect::<String>()
+            };
+            format!(
+                "{} | {}",
+                lines(order.into_iter().map(pattern), " "),
+                lines(outputs.into_iter().map(pattern), " ")
+            )
+        })
+    }
+
+    fn day9_input() -> impl Strategy<Value = String> {
+        digits(
+            1usize..10,
+            1usize..10,
+            prop_oneof![3 => 0u8..9, 1 => Just(9)],
+        )
+    }
+
+    /// Chunks mostly closed in order, with a few wrong closing characters
+    fn day10_input() -> impl Strategy<Value = String> {
+        let line = vec((0u8..10, 0usize..4), 1..40).prop_map(|actions| {
+            let (open, close) = (['(', '[', '{', '<'], [')', ']', '}', '>']);
+            let mut stack = Vec::new();
+            let mut line = String::new();
+            for (action, kind) in actions {
+                match stack.last() {
+                    Some(&top) if action == 9 => line.push(close[(top + 1 + kind % 3) % 4]),
+                    Some(_) if action >= 5 => line.push(close[stack.pop().unwrap()]),
+                    _ => {
+                        stack.push(kind);
+                        line.push(open[kind]);
+                    }
+                }
+            }
+            line
+        });
+        vec(line, 1..10).prop_map(|l| lines(l, "\n"))
+    }
+
+    fn day11_input() -> impl Strategy<Value = String> {
+        digits(1usize..6, 1usize..6, 0u8..=9)
+    }
+
+    /// Links between a few caves, never between two big ones
+    fn day12_input() -> impl Strategy<Value = String> {
+        const CAVES: [&str; 8] = ["start", "end", "a", "b", "c", "d", "A", "B"];
+        vec((0..CAVES.len(), 0..CAVES.len()), 1..10).prop_map(|links| {
+            let links = links
+                .into_iter()
+                .filter(|&(a, b)| a != b && (a < 6 || b < 6))
+                .map(|(a, b)| format!("{}-{}", CAVES[a], CAVES[b]));
+            lines(links, "\n")
+        })
+    }
+
+    /// Folds in the middle of the sheet, no dot ever being on a fold line
+    fn day13_input() -> impl Strategy<Value = String> {
+        let folds = vec(any::<bool>(), 1..5);
+        (1i32..4, 1i32..4, folds).prop_flat_map(|(mut width, mut height, folds)| {
+            for &along_x in folds.iter().rev() {
+                match along_x {
+                    true => width = 2 * width + 1,
+                    false => height = 2 * height + 1,
+                }
+            }
+            let mut instructions = Vec::new();
+            let (mut w, mut h) = (width, height);
+            for along_x in folds {
+                if along_x {
+                    w /= 2;
+                    instructions.push(FoldInstruction::X(w));
+                } else {
+                    h /= 2;
+                    instructions.push(FoldInstruction::Y(h));
+                }
+            }
+            vec((0..width, 0..height), 1..30).prop_map(move |points| {
+                let mut kept: Vec<String> = points
+                    .iter()
+                    .filter(|&&(x, y)| {
+                        let mut p = Point2::new(x, y);
+                        instructions.iter().all(|fold| {
+                            let on_line = match *fold {
+                                FoldInstruction::X(i) => p.x == i,
+                                FoldInstruction::Y(i) => p.y == i,
+                            };
+                            fold_points(std::slice::from_mut(&mut p), fold);
+                            !on_line
+                        })
+                    })
+                    .map(|(x, y)| format!("{},{}", x, y))
+                    .collect();
+                if kept.is_empty() {
+                    kept.push("0,0".to_string());
+                }
+                let folds = instructions.iter().map(|fold| match fold {
+                    FoldInstruction::X(i) => format!("fold along x={}", i),
+                    FoldInstruction::Y(i) => format!("fold along y={}", i),
+                });
+                format!("{}\n\n{}", lines(kept, "\n"), lines(folds, "\n"))
+            })
+        })
+    }
+
+    /// Rules for every pair of a few elements, so that polymers never get stuck
+    fn day14_input() -> impl Strategy<Value = String> {
+        const ELEMENTS: [char; 4] = ['N', 'C', 'B', 'H'];
+        (2usize..=4).prop_flat_map(|count| {
+            (vec(0..count, 2..6), vec(0..count, count * count)).prop_map(
+                move |(template, inserted)| {
+                    let rules = inserted.iter().enumerate().map(|(idx, &c)| {
+                        let (a, b) = (idx / count, idx % count);
+                        format!("{}{} -> {}", ELEMENTS[a], ELEMENTS[b], ELEMENTS[c])
+                    });
+                    let template: String = template.iter().map(|&e| ELEMENTS[e]).collect();
+                    format!("{}\n\n{}", template, lines(rules, "\n"))
+                },
+            )
+        })
+    }
+
+    fn day15_input() -> impl Strategy<Value = String> {
+        digits(1usize..8, 1usize..8, 1u8..=9)
+    }
+
+    /// Packets nested a few levels deep, with the right number of sub-packets for their kind
+    fn packet() -> impl Strategy<Value = Packet> {
+        let literal = prop_oneof![0u64..16, 0u64..1 << 20, any::<u64>()];
+        let literal = (0u8..8, literal).prop_map(|(version, x)| Packet {
+            version,
+            kind: PacketKind::Literal(x),
+        });
+        literal.prop_recursive(3, 24, 4, |inner| {
+            let kinds = [
+                OperatorKind::Sum,
+                OperatorKind::Product,
+                OperatorKind::Min,
+                OperatorKind::Max,
+                OperatorKind::Greater,
+                OperatorKind::Less,
+                OperatorKind::Equal,
+            ];
+            let kind = prop::sample::select(kinds.to_vec());
+            (0u8..8, kind, vec(inner, 1..4)).prop_map(|(version, kind, mut packets)| {
+                if let OperatorKind::Greater | OperatorKind::Less | OperatorKind::Equal = kind {
+                    packets.resize(2, packets[0].clone());
+                }
+                Packet {
+                    version,
+                    kind: PacketKind::Operator(OperatorPacket { kind, packets }),
+                }
+            })
+        })
+    }
+
+    fn push_bits(bits: &mut String, value: u64, count: usize) {
+        (0..count)
+            .rev()
+            .for_each(|i| bits.push(if value >> i & 1 == 1 { '1' } else { '0' }));
+    }
+
+    /// Writes a packet in binary, alternating length types so that both are covered
+    fn encode(packet: &Packet, bits: &mut String) {
+        push_bits(bits, packet.version as u64, 3);
+        match &packet.kind {
+            PacketKind::Literal(x) => {
+                push_bits(bits, 4, 3);
+                let groups = (64 - x.leading_zeros() as usize).div_ceil(4).max(1);
+                for group in (0..groups).rev() {
+                    push_bits(bits, (group > 0) as u64, 1);
+                    push_bits(bits, x >> (group * 4) & 0xF, 4);
+                }
+            }
+            PacketKind::Operator(OperatorPacket { kind, packets }) => {
+                let type_id = match kind {
+                    OperatorKind::Sum => 0,
+                    OperatorKind::Product => 1,
+                    OperatorKind::Min => 2,
+                    OperatorKind::Max => 3,
+                    OperatorKind::Greater => 5,
+                    OperatorKind::Less => 6,
+                    OperatorKind::Equal => 7,
+                };
+                push_bits(bits, type_id, 3);
+                let mut content = String::new();
+                packets.iter().for_each(|p| encode(p, &mut content));
+                if (packet.version as usize + packets.len()).is_multiple_of(2) {
+                    push_bits(bits, 0, 1);
+                    push_bits(bits, content.len() as u64, 15);
+                } else {
+                    push_bits(bits, 1, 1);
+                    push_bits(bits, packets.len() as u64, 11);
+                }
+                *bits += &content;
+            }
+        }
+    }
+
+    fn to_hex(packet: &Packet) -> String {
+        let mut bits = String::new();
+        encode(packet, &mut bits);
+        while !bits.len().is_multiple_of(4) {
+            bits.push('0');
+        }
+        let digits = bits.as_bytes().chunks(4).map(|digit| {
+            let digit = u32::from_str_radix(std::str::from_utf8(digit).unwrap(), 2).unwrap();
+            std::char::from_digit(digit, 16)
+                .unwrap()
+                .to_ascii_uppercase()
+        });
+        digits.collect()
+    }
+
+    proptest! {
+        #![proptest_config(config(1000))]
+
+        #[test]
+        fn day1_agrees(input in day1_input()) {
+            let depths = day1::ints(&input)?;
+            prop_assert_eq!(day1::part1(&depths), super::day1::part1(&depths));
+            prop_assert_eq!(day1::part2(&depths), super::day1::part2(&depths));
+        }
+
+        #[test]
+        fn day2_agrees(input in day2_input()) {
+            let instructions = day2::parse(&input)?;
+            prop_assert_eq!(day2::part1(&instructions)?, super::day2::part1(&instructions));
+            prop_assert_eq!(day2::part2(&instructions)?, super::day2::part2(&instructions));
+        }
+
+        #[test]
+        fn day3_agrees(input in day3_input()) {
+            let numbers = day3::parse(&input)?;
+            prop_assert_eq!(day3::part1(&numbers), super::day3::part1(&numbers));
+            prop_assert_eq!(day3::part2(&numbers).ok(), super::day3::part2(&numbers).ok());
+        }
+
+        #[test]
+        fn day4_agrees(input in day4_input()) {
+            let bingo = day4::parse(&input)?;
+            prop_assert_eq!(day4::part1(&bingo).ok(), super::day4::part1(&bingo).ok());
+            prop_assert_eq!(day4::part2(&bingo).ok(), super::day4::part2(&bingo).ok());
+        }
+
+        #[test]
+        fn day5_agrees(input in day5_input()) {
+            let segments = day5::parse(&input)?;
+            prop_assert_eq!(day5::part1(&segments), super::day5::part1(&segments));
+            prop_assert_eq!(day5::part2(&segments), super::day5::part2(&segments));
+        }
+
+        #[test]
+        fn day6_agrees(input in day6_input(), days in 0usize..=80) {
+            let timers = day6::parse(&input)?;
+            prop_assert_eq!(
+                day6::fishes_for_day(&timers, days)?,
+                super::day6::fishes_for_day(&timers, days)
+            );
+        }
+
+        #[test]
+        fn day7_agrees(input in day7_input()) {
+            let crabs = day7::parse(&input)?;
+            prop_assert_eq!(day7::part1(&crabs)?, super::day7::part1(&crabs));
+            prop_assert_eq!(day7::part2(&crabs)?, super::day7::part2(&crabs));
+            prop_assert_eq!(day7::median_alignment(&crabs)?, super::day7::part1(&crabs));
+            prop_assert_eq!(day7::mean_alignment(&crabs)?, super::day7::part2(&crabs));
+        }
+
+        #[test]
+        fn day9_agrees(input in day9_input()) {
+            let heightmap = day9::parse(&input)?;
+            prop_assert_eq!(day9::part1(&heightmap), super::day9::part1(&heightmap));
+            prop_assert_eq!(day9::part2(&heightmap), super::day9::part2(&heightmap));
+        }
+
+        #[test]
+        fn day10_agrees(input in day10_input()) {
+            let lines = day10::parse(&input)?;
+            prop_assert_eq!(day10::part1(&lines), super::day10::part1(&lines));
+            prop_assert_eq!(day10::part2(&lines).ok(), super::day10::part2(&lines).ok());
+        }
+
+        #[test]
+        fn day12_agrees(input in day12_input()) {
+            let caves = day12::parse(&input)?;
+            prop_assert_eq!(day12::part1(&caves)?, super::day12::part1(&caves));
+            prop_assert_eq!(day12::part2(&caves)?, super::day12::part2(&caves));
+        }
+
+        #[test]
+        fn day13_agrees(input in day13_input()) {
+            let paper = day13::parse(&input)?;
+            prop_assert_eq!(day13::part1(&paper)?, super::day13::part1(&paper)?);
+            prop_assert_eq!(day13::part2(&paper), super::day13::part2(&paper));
+        }
+
+        #[test]
+        fn day14_agrees(input in day14_input(), steps in 0usize..=10) {
+            let template = day14::parse(&input)?;
+            prop_assert_eq!(
+                template.score_after(steps)?,
+                super::day14::score_after(&template, steps)?
+            );
+        }
+
+        #[test]
+        fn day16_agrees(packet in packet()) {
+            let hex = to_hex(&packet);
+            prop_assert_eq!(&hex.parse::<Packet>()?, &packet);
+            let bits = day16::parse(&hex)?;
+            prop_assert_eq!(day16::part1(&bits)?, super::day16::part1(&bits)?);
+            // The solver doesn't check for overflows
+            if let Ok(value) = super::day16::part2(&bits) {
+                prop_assert_eq!(day16::part2(&bits)?, value);
+            }
+        }
+    }
+
+    // Slower oracles, checked on fewer cases
+    proptest! {
+        #![proptest_config(config(100))]
+
+        #[test]
+        fn day8_agrees(notes in vec(day8_note(), 1..4)) {
+            let notes = day8::parse(&lines(notes, "\n"))?;
+            prop_assert_eq!(day8::part1(&notes), super::day8::part1(&notes));
+            prop_assert_eq!(day8::part2(&notes)?, super::day8::part2(&notes)?);
+        }
+
+        #[test]
+        fn day11_agrees(input in day11_input()) {
+            let octopuses = day11::parse(&input)?;
+            prop_assert_eq!(day11::part1(&octopuses), super::day11::part1(&octopuses));
+            prop_assert_eq!(day11::part2(&octopuses).ok(), super::day11::part2(&octopuses).ok());
+        }
+
+        #[test]
+        fn day15_agrees(input in day15_input()) {
+            let chitons = day15::parse(&input)?;
+            prop_assert_eq!(day15::part1(&chitons), super::day15::part1(&chitons));
+            prop_assert_eq!(day15::part2(&chitons), super::day15::part2(&chitons));
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//! Machine-readable reports of a run, for CI dashboards and regression scripts.
+//! Times are in nanoseconds. Days whose input couldn't be read or parsed have an `error` and no
+//! parts, parts that failed have an `error` and no `answer`. Parts also come with the answer
+//! stored in `answers.toml`, if any, and the report isn't `ok` when they differ.
+//! Allocations are only reported with the `count-allocs` feature.
+
+use crate::answers::Answers;
+use crate::memory::AllocStats;
+use crate::runner::DayResult;
+use serde::Serialize;
+use std::fmt::Display;
+use std::time::Duration;
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct Report {
+    pub ok: bool,
+    pub days: Vec<DayReport>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct DayReport {
+    pub day: u8,
+    pub title: Option<&'static str>,
+    pub parse_time_ns: Option<u64>,
+    pub parse_allocs: Option<AllocStats>,
+    pub error: Option<String>,
+    pub parts: Vec<PartReport>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct PartReport {
+    pub part: u8,
+    pub answer: Option<String>,
+    pub expected: Option<String>,
+    pub solve_time_ns: u64,
+    pub allocs: Option<AllocStats>,
+    pub error: Option<String>,
+}
+
+const CSV_HEADER: &str = "day,part,answer,expected,parse_time_ns,solve_time_ns,error,\
+parse_allocations,parse_bytes,parse_peak_bytes,allocations,bytes,peak_bytes";
+
+fn nanos(time: Duration) -> u64 {
+    time.as_nanos().try_into().unwrap_or(u64::MAX)
+}
+
+/// Allocations, bytes and peak bytes as CSV fields, empty if they weren't counted
+fn csv_allocs(stats: Option<AllocStats>) -> String {
+    match stats {
+        Some(s) => format!("{},{},{}", s.allocations, s.bytes, s.peak_bytes),
+        None => ",,".to_string(),
+    }
+}
+
+/// Quotes a CSV field when needed, as multiline answers (day 13) are common
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+impl DayReport {
+    pub fn new<E: Display>(day: u8, result: &Result<DayResult, E>, answers: &Answers) -> Self {
+        let title = crate::solver::solver(day).map(|s| s.title());
+        match result {
+            Ok(result) => DayReport {
+                day,
+                title,
+                parse_time_ns: Some(nanos(result.parse_time)),
+                parse_allocs: result.parse_allocs,
+                error: None,
+                parts: result
+                    .parts
+                    .iter()
+                    .map(|part| PartReport {
+                        part: part.part,
+                        answer: part.answer.as_ref().ok().cloned(),
+                        expected: answers.get(day, part.part).map(str::to_string),
+                        solve_time_ns: nanos(part.time),
+                        allocs: part.allocs,
+                        error: part.answer.as_ref().err().map(|e| e.to_string()),
+                    })
+                    .collect(),
+            },
+            Err(e) => DayReport {
+                day,
+                title,
+                parse_time_ns: None,
+                parse_allocs: None,
+                error: Some(e.to_string()),
+                parts: Vec::new(),
+            },
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none() && self.parts.iter().all(PartReport::is_ok)
+    }
+}
+
+impl PartReport {
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none() && (self.expected.is_none() || self.expected == self.answer)
+    }
+}
+
+impl Report {
+    pub fn new<E: Display>(results: &[(u8, Result<DayResult, E>)], answers: &Answers) -> Self {
+        let days: Vec<DayReport> = results
+            .iter()
+            .map(|(day, result)| DayReport::new(*day, result, answers))
+            .collect();
+        Report {
+            ok: days.iter().all(DayReport::is_ok),
+            days,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Reports always serialize")
+    }
+
+    /// One row per part, or per day when the day itself failed
+    pub fn to_csv(&self) -> String {
+        let mut csv = CSV_HEADER.to_string();
+        for day in &self.days {
+            let parse_time = day.parse_time_ns.map(|t| t.to_string()).unwrap_or_default();
+            let parse_allocs = csv_allocs(day.parse_allocs);
+            if let Some(error) = &day.error {
+                csv += &format!(
+                    "\n{},,,,{},,{},{},,,",
+                    day.day,
+                    parse_time,
+                    csv_field(error),
+                    parse_allocs
+                );
+            }
+            for part in &day.parts {
+                csv += &format!(
+                    "\n{},{},{},{},{},{},{},{},{}",
+                    day.day,
+                    part.part,
+                    csv_field(part.answer.as_deref().unwrap_or("")),
+                    csv_field(part.expected.as_deref().unwrap_or("")),
+                    parse_time,
+                    part.solve_time_ns,
+                    csv_field(part.error.as_deref().unwrap_or("")),
+                    parse_allocs,
+                    csv_allocs(part.allocs),
+                );
+            }
+        }
+        csv + "\n"
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::error::AocError;
+    use crate::runner;
+
+    fn report() -> Report {
+        let mut answers = Answers::default();
+        answers.set(7, 1, "37".to_string());
+        Report::new(
+            &[
+                (7, runner::run_day(7, "16,1,2,0,4,2,7,1,2,14", &[1, 3])),
+                (6, runner::run_day(6, "9", &[1])),
+            ],
+            &answers,
+        )
+    }
+
+    #[test]
+    fn should_report_answers_and_errors() {
+        let report = report();
+        assert!(!report.ok);
+        assert_eq!(report.days[0].title, Some("The Treachery of Whales"));
+        assert_eq!(report.days[0].parts[0].answer.as_deref(), Some("37"));
+        assert!(report.days[0].parts[0].is_ok());
+        assert_eq!(
+            report.days[0].parts[1].error,
+            Some(AocError::UnknownPart(3).to_string())
+        );
+        assert!(report.days[1].parse_time_ns.is_none());
+        assert!(report.days[1].error.is_some());
+    }
+
+    #[test]
+    fn should_report_wrong_answers() {
+        let mut answers = Answers::default();
+        answers.set(7, 2, "169".to_string());
+        let results = [(7, runner::run_day(7, "16,1,2,0,4,2,7,1,2,14", &[1, 2]))];
+        assert!(!Report::new(&results, &answers).ok);
+        assert!(Report::new(&results, &Answers::default()).ok);
+    }
+
+    #[test]
+    fn should_serialize() -> Result<(), serde_json::Error> {
+        let report = report();
+        let json: serde_json::Value = serde_json::from_str(&report.to_json())?;
+        assert_eq!(json["days"][0]["parts"][0]["answer"], "37");
+        assert_eq!(json["days"][1]["parts"], serde_json::json!([]));
+
+        let csv = report.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], CSV_HEADER);
+        assert!(lines[1].starts_with("7,1,37,37,"));
+        assert!(lines[3].starts_with("6,,,,,,"));
+        assert!(lines[..3].iter().all(|l| l.matches(',').count() == 12));
+        assert_eq!(csv_field("a\"b\nc"), "\"a\"\"b\nc\"");
+        Ok(())
+    }
+}
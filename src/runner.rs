@@ -0,0 +1,374 @@
+//! Runs solvers without `cargo aoc`: parses an input, solves the requested parts and times both.
+
+#[cfg(feature = "cache")]
+use crate::cache::Cache;
+use crate::error::AocError;
+use crate::memory::{self, AllocStats};
+use crate::solver::{Parsed, Solver, DEFAULT_VARIANT};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const DAYS: std::ops::RangeInclusive<u8> = 1..=16;
+
+/// Directory holding the real inputs as `dayN.txt`, the layout used by `cargo aoc`
+pub const INPUT_DIR: &str = "input/2021";
+
+#[derive(Debug)]
+pub struct PartResult {
+    pub part: u8,
+    pub answer: Result<String, AocError>,
+    pub time: Duration,
+    /// Only counted with the `count-allocs` feature
+    pub allocs: Option<AllocStats>,
+}
+
+#[derive(Debug)]
+pub struct DayResult {
+    pub day: u8,
+    pub parse_time: Duration,
+    pub parse_allocs: Option<AllocStats>,
+    pub parts: Vec<PartResult>,
+}
+
+/// Solver outputs that can be reported as an answer
+pub trait IntoAnswer {
+    fn into_answer(self) -> Result<String, AocError>;
+}
+
+macro_rules! impl_into_answer {
+    ($($t:ty),*) => {
+        $(
+            impl IntoAnswer for $t {
+                fn into_answer(self) -> Result<String, AocError> {
+                    Ok(self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+impl_into_answer!(i32, i64, u32, u64, usize, String);
+
+impl<T: IntoAnswer> IntoAnswer for Result<T, AocError> {
+    fn into_answer(self) -> Result<String, AocError> {
+        self.and_then(IntoAnswer::into_answer)
+    }
+}
+
+pub fn input_path(dir: &Path, day: u8) -> PathBuf {
+    dir.join(format!("day{}.txt", day))
+}
+
+/// Runs `day` on `input`. Only the parts listed in `parts` are solved.
+pub fn run_day(day: u8, input: &str, parts: &[u8]) -> Result<DayResult, AocError> {
+    crate::solver::solver(day)
+        .ok_or(AocError::UnknownDay(day))?
+        .run(input, parts)
+}
+
+/// How `run_all` spreads the work
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunOptions {
+    /// Number of days running at the same time
+    pub threads: usize,
+    /// Parsing and each part are given up on after this long
+    pub timeout: Duration,
+    /// Implementation solving the parts, see `solver::Variant`
+    pub variant: String,
+    /// Where parsed inputs are reused from and stored, see the `cache` module
+    #[cfg(feature = "cache")]
+    pub cache: Option<Cache>,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        RunOptions {
+            threads: thread::available_parallelism().map_or(4, |n| n.get()),
+            timeout: Duration::from_secs(60),
+            variant: DEFAULT_VARIANT.to_string(),
+            #[cfg(feature = "cache")]
+            cache: None,
+        }
+    }
+}
+
+/// Runs `f` on its own thread, giving up on it after `limit`. Threads can't be killed, so one
+/// that times out keeps running in the background until it's done or the process exits.
+fn with_timeout<T: Send + 'static>(
+    day: u8,
+    limit: Duration,
+    f: impl FnOnce() -> T + Send + 'static,
+) -> Result<(T, Duration), AocError> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let start = Instant::now();
+        let result = f();
+        // The receiver is gone if we timed out, nobody is interested anymore
+        let _ = sender.send((result, start.elapsed()));
+    });
+    receiver.recv_timeout(limit).map_err(|e| match e {
+        mpsc::RecvTimeoutError::Timeout => AocError::TimedOut { day, limit },
+        mpsc::RecvTimeoutError::Disconnected => AocError::Panicked(day),
+    })
+}
+
+/// Solves `part` with the implementation called `variant`, giving up on it after `limit`
+fn run_part(
+    solver: &Arc<dyn Solver>,
+    parsed: &Arc<Parsed>,
+    variant: &str,
+    part: u8,
+    limit: Duration,
+) -> PartResult {
+    let day = solver.day();
+    let (solver, parsed, variant) = (solver.clone(), parsed.clone(), variant.to_string());
+    let solve = move || memory::measure(|| solver.variant_part(&variant, part, &parsed));
+    match with_timeout(day, limit, solve) {
+        Ok(((answer, allocs), time)) => PartResult {
+            part,
+            answer,
+            time,
+            allocs,
+        },
+        Err(e) => PartResult {
+            part,
+            answer: Err(e),
+            time: limit,
+            allocs: None,
+        },
+    }
+}
+
+fn run_with_timeout(
+    solver: Arc<dyn Solver>,
+    input: String,
+    parts: &[u8],
+    options: &RunOptions,
+) -> Result<DayResult, AocError> {
+    let day = solver.day();
+    let parser = solver.clone();
+    #[cfg(feature = "cache")]
+    let cache = options.cache.clone();
+    let ((parsed, parse_allocs), parse_time) = with_timeout(day, options.timeout, move || {
+        #[cfg(feature = "cache")]
+        if let Some(cache) = &cache {
+            return memory::measure(|| parser.parse_cached(&input, cache).map(|(p, _)| p));
+        }
+        memory::measure(|| parser.parse(&input))
+    })?;
+    let parsed = Arc::new(parsed?);
+
+    let parts = parts
+        .iter()
+        .map(|&part| run_part(&solver, &parsed, &options.variant, part, options.timeout))
+        .collect();
+
+    Ok(DayResult {
+        day,
+        parse_time,
+        parse_allocs,
+        parts,
+    })
+}
+
+/// Runs each `(day, input)` pair concurrently, solving the parts listed in `parts`.
+/// Results come in the order of `inputs`, a day or part that takes longer than the timeout
+/// failing with `AocError::TimedOut` instead of holding up the others.
+pub fn run_all(
+    inputs: Vec<(u8, String)>,
+    parts: &[u8],
+    options: RunOptions,
+) -> Vec<(u8, Result<DayResult, AocError>)> {
+    let count = inputs.len();
+    let queue = Mutex::new(inputs.into_iter().enumerate());
+    let results = Mutex::new(Vec::with_capacity(count));
+    thread::scope(|scope| {
+        for _ in 0..options.threads.clamp(1, count.max(1)) {
+            scope.spawn(|| loop {
+                let next = queue.lock().expect("Queue lock poisoned").next();
+                let Some((idx, (day, input))) = next else {
+                    break;
+                };
+                let result = match crate::solver::solver(day) {
+                    Some(solver) => run_with_timeout(solver.into(), input, parts, &options),
+                    None => Err(AocError::UnknownDay(day)),
+                };
+                results
+                    .lock()
+                    .expect("Results lock poisoned")
+                    .push((idx, (day, result)));
+            });
+        }
+    });
+
+    let mut results = results.into_inner().expect("Results lock poisoned");
+    results.sort_by_key(|(idx, _)| *idx);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Answers of every implementation of the parts of a day, from the same parsed input
+#[derive(Debug)]
+pub struct Verification {
+    pub day: u8,
+    pub parse_time: Duration,
+    /// For each part, the result of each of its variants, the default one first
+    pub parts: Vec<Vec<(&'static str, PartResult)>>,
+}
+
+impl Verification {
+    /// Parts whose variants gave different answers. Finding no solution counts as an answer,
+    /// timing out or panicking doesn't.
+    pub fn disagreements(&self) -> Vec<u8> {
+        let answer = |result: &PartResult| match &result.answer {
+            Err(AocError::TimedOut { .. } | AocError::Panicked(_)) => None,
+            answer => Some(answer.as_ref().ok().cloned()),
+        };
+        self.parts
+            .iter()
+            .filter(|results| {
+                let mut answers = results.iter().filter_map(|(_, r)| answer(r));
+                let first = answers.next();
+                answers.any(|a| Some(a) != first)
+            })
+            .filter_map(|results| results.first().map(|(_, r)| r.part))
+            .collect()
+    }
+}
+
+/// Solves the parts listed in `parts` with every variant of `day`, so that they can be checked
+/// against each other. Each variant is given up on after `limit`.
+pub fn verify(
+    day: u8,
+    input: String,
+    parts: &[u8],
+    limit: Duration,
+) -> Result<Verification, AocError> {
+    let solver: Arc<dyn Solver> = crate::solver::solver(day)
+        .ok_or(AocError::UnknownDay(day))?
+        .into();
+    let parser = solver.clone();
+    let (parsed, parse_time) = with_timeout(day, limit, move || parser.parse(&input))?;
+    let parsed = Arc::new(parsed?);
+
+    let parts = parts
+        .iter()
+        .map(|&part| {
+            let variants = solver.variants(part).into_iter();
+            variants
+                .map(|variant| (variant, run_part(&solver, &parsed, variant, part, limit)))
+                .collect()
+        })
+        .collect();
+
+    Ok(Verification {
+        day,
+        parse_time,
+        parts,
+    })
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn should_run_selected_parts() -> Result<(), AocError> {
+        let result = run_day(7, "16,1,2,0,4,2,7,1,2,14", &[2])?;
+        assert_eq!(result.day, 7);
+        assert_eq!(result.parts.len(), 1);
+        assert_eq!(result.parts[0].answer, Ok("168".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn should_reject_unknown_days_and_parts() -> Result<(), AocError> {
+        assert_eq!(run_day(26, "", &[1]).err(), Some(AocError::UnknownDay(26)));
+        let result = run_day(6, "3,4,3,1,2", &[3])?;
+        assert_eq!(result.parts[0].answer, Err(AocError::UnknownPart(3)));
+        Ok(())
+    }
+
+    #[test]
+    fn should_run_all_in_order() {
+        let inputs = vec![
+            (7, "16,1,2,0,4,2,7,1,2,14".to_string()),
+            (6, "3,4,3,1,2".to_string()),
+            (26, String::new()),
+            (1, "x".to_string()),
+        ];
+        let options = RunOptions {
+            threads: 2,
+            ..RunOptions::default()
+        };
+        let results = run_all(inputs, &[1, 2], options);
+        let days: Vec<u8> = results.iter().map(|(day, _)| *day).collect();
+        assert_eq!(days, vec![7, 6, 26, 1]);
+        let answers = |idx: usize| -> Vec<Option<&str>> {
+            let result = results[idx].1.as_ref().expect("Day should run");
+            result
+                .parts
+                .iter()
+                .map(|p| p.answer.as_deref().ok())
+                .collect()
+        };
+        assert_eq!(answers(0), vec![Some("37"), Some("168")]);
+        assert_eq!(answers(1)[1], Some("26984457539"));
+        assert_eq!(results[2].1.as_ref().err(), Some(&AocError::UnknownDay(26)));
+        assert!(matches!(results[3].1, Err(AocError::Parse { day: 1, .. })));
+    }
+
+    #[test]
+    fn should_run_selected_variant() {
+        let inputs = vec![(7, "16,1,2,0,4,2,7,1,2,14".to_string())];
+        let options = RunOptions {
+            variant: "median".to_string(),
+            ..RunOptions::default()
+        };
+        let results = run_all(inputs, &[1, 2], options);
+        let result = results[0].1.as_ref().expect("Day should run");
+        assert_eq!(result.parts[0].answer, Ok("37".to_string()));
+        assert!(matches!(
+            result.parts[1].answer,
+            Err(AocError::UnknownVariant {
+                day: 7,
+                part: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn should_verify_variants() -> Result<(), AocError> {
+        let limit = Duration::from_secs(10);
+        let verification = verify(7, "16,1,2,0,4,2,7,1,2,14".to_string(), &[1, 2], limit)?;
+        let names: Vec<&str> = verification.parts[0]
+            .iter()
+            .map(|(name, _)| *name)
+            .collect();
+        assert_eq!(names, vec![DEFAULT_VARIANT, "median", "naive"]);
+        assert!(verification.parts[1]
+            .iter()
+            .all(|(_, r)| r.part == 2 && r.answer == Ok("168".to_string())));
+        assert!(verification.disagreements().is_empty());
+
+        let mut verification = verify(7, "3,5".to_string(), &[1], limit)?;
+        verification.parts[0][1].1.answer = Ok("1".to_string());
+        assert_eq!(verification.disagreements(), vec![1]);
+        verification.parts[0][1].1.answer = Err(AocError::TimedOut { day: 7, limit });
+        assert!(verification.disagreements().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn should_time_out() {
+        let limit = Duration::from_millis(10);
+        let slow = with_timeout(12, limit, || thread::sleep(Duration::from_secs(1)));
+        assert_eq!(slow, Err(AocError::TimedOut { day: 12, limit }));
+        let fast = with_timeout(12, limit, || 42).map(|(x, _)| x);
+        assert_eq!(fast, Ok(42));
+        let panicking = with_timeout(12, Duration::from_secs(5), || panic!("Broken solver"));
+        assert_eq!(panicking.map(|_: ((), _)| ()), Err(AocError::Panicked(12)));
+    }
+}
@@ -0,0 +1,366 @@
+//! Programmatic access to the days: a `Solver` for each of them and the `solvers()` registry.
+//! Besides its default implementation, a day can have named `Variant`s solving its parts
+//! another way, which `runner::verify` checks against each other.
+
+#[cfg(feature = "cache")]
+use crate::cache::Cache;
+use crate::error::AocError;
+#[cfg(feature = "cache")]
+use crate::input::normalize;
+use crate::memory;
+use crate::runner::{DayResult, IntoAnswer, PartResult};
+use crate::trace::span;
+use std::any::Any;
+use std::time::Instant;
+
+/// Name of the implementation used by `Solver::part1` and `Solver::part2`
+pub const DEFAULT_VARIANT: &str = "default";
+
+/// Input of a day once parsed. Only meant to be handed back to the solver that parsed it.
+pub type Parsed = Box<dyn Any + Send + Sync>;
+
+/// Parsed inputs that can be stored in a `Cache`: all of them with the `cache` feature, which
+/// requires them to be serializable, and no bound at all without it
+#[cfg(feature = "cache")]
+pub trait Cacheable: serde::Serialize + serde::de::DeserializeOwned {}
+#[cfg(feature = "cache")]
+impl<T: serde::Serialize + serde::de::DeserializeOwned> Cacheable for T {}
+#[cfg(not(feature = "cache"))]
+pub trait Cacheable {}
+#[cfg(not(feature = "cache"))]
+impl<T> Cacheable for T {}
+
+/// An example from the puzzle text, along with the answers it gives
+#[derive(Debug, Clone, Copy)]
+pub struct Example {
+    pub input: &'static str,
+    pub part1: Option<&'static str>,
+    pub part2: Option<&'static str>,
+}
+
+pub trait Solver: Send + Sync {
+    fn day(&self) -> u8;
+
+    fn title(&self) -> &'static str;
+
+    fn examples(&self) -> &'static [Example];
+
+    /// Parses `input`, which the day's generator normalizes first, see `input::normalize`
+    fn parse(&self, input: &str) -> Result<Parsed, AocError>;
+
+    /// Same as `parse`, loading the parsed input from `cache` if it was stored there for the
+    /// same input, and storing it there otherwise. Also returns whether it was loaded. Failing
+    /// to store it isn't an error, the input is just parsed again next time.
+    #[cfg(feature = "cache")]
+    fn parse_cached(&self, input: &str, cache: &Cache) -> Result<(Parsed, bool), AocError>;
+
+    /// Panics if `parsed` doesn't come from this solver's `parse`.
+    fn part1(&self, parsed: &Parsed) -> Result<String, AocError>;
+
+    /// Panics if `parsed` doesn't come from this solver's `parse`.
+    fn part2(&self, parsed: &Parsed) -> Result<String, AocError>;
+
+    fn part(&self, part: u8, parsed: &Parsed) -> Result<String, AocError> {
+        match part {
+            1 => self.part1(parsed),
+            2 => self.part2(parsed),
+            x => Err(AocError::UnknownPart(x)),
+        }
+    }
+
+    /// Names of the implementations of `part`, starting with `DEFAULT_VARIANT`
+    fn variants(&self, part: u8) -> Vec<&'static str>;
+
+    /// Solves `part` with the implementation called `variant`.
+    /// Panics if `parsed` doesn't come from this solver's `parse`.
+    fn variant_part(&self, variant: &str, part: u8, parsed: &Parsed) -> Result<String, AocError>;
+
+    /// Parses `input` then solves the parts listed in `parts`, timing each step and counting
+    /// its allocations with the `count-allocs` feature.
+    fn run(&self, input: &str, parts: &[u8]) -> Result<DayResult, AocError> {
+        let start = Instant::now();
+        let (parsed, parse_allocs) = memory::measure(|| self.parse(input));
+        let parse_time = start.elapsed();
+        let parsed = parsed?;
+
+        let parts = parts
+            .iter()
+            .map(|&part| {
+                let start = Instant::now();
+                let (answer, allocs) = memory::measure(|| self.part(part, &parsed));
+                PartResult {
+                    part,
+                    answer,
+                    time: start.elapsed(),
+                    allocs,
+                }
+            })
+            .collect();
+
+        Ok(DayResult {
+            day: self.day(),
+            parse_time,
+            parse_allocs,
+            parts,
+        })
+    }
+}
+
+/// Another implementation of the parts of a day, solving them from the same parsed input
+pub struct Variant<T, A, B> {
+    pub name: &'static str,
+    /// `None` when the variant doesn't solve this part
+    pub part1: Option<fn(&T) -> A>,
+    pub part2: Option<fn(&T) -> B>,
+}
+
+/// `Solver` made of a day's generator and parts
+pub struct Solution<T, A, B> {
+    pub day: u8,
+    pub title: &'static str,
+    pub examples: &'static [Example],
+    pub parse: fn(&str) -> Result<T, AocError>,
+    /// Whether a parsed input upholds what `parse` guarantees, which parsed inputs loaded from a
+    /// cache are checked against so that corrupted or stale entries are parsed again
+    pub valid: fn(&T) -> bool,
+    pub part1: fn(&T) -> A,
+    pub part2: fn(&T) -> B,
+    pub variants: Vec<Variant<T, A, B>>,
+}
+
+impl<T, A, B> Solution<T, A, B>
+where
+    T: Any,
+{
+    fn input<'a>(&self, parsed: &'a Parsed) -> &'a T {
+        parsed
+            .downcast_ref()
+            .unwrap_or_else(|| panic!("Input wasn't parsed by the solver of day {}", self.day))
+    }
+}
+
+impl<T, A, B> Solver for Solution<T, A, B>
+where
+    T: Any + Send + Sync + Cacheable,
+    A: IntoAnswer,
+    B: IntoAnswer,
+{
+    fn day(&self) -> u8 {
+        self.day
+    }
+
+    fn title(&self) -> &'static str {
+        self.title
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        self.examples
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed, AocError> {
+        let _span = span!("parse", day = self.day);
+        Ok(Box::new((self.parse)(input)?))
+    }
+
+    #[cfg(feature = "cache")]
+    fn parse_cached(&self, input: &str, cache: &Cache) -> Result<(Parsed, bool), AocError> {
+        let input = normalize(input);
+        if let Some(parsed) = cache.load(self.day, &input).filter(self.valid) {
+            return Ok((Box::new(parsed), true));
+        }
+        let _span = span!("parse", day = self.day);
+        let parsed = (self.parse)(&input)?;
+        let _ = cache.store(self.day, &input, &parsed);
+        Ok((Box::new(parsed), false))
+    }
+
+    fn part1(&self, parsed: &Parsed) -> Result<String, AocError> {
+        let _span = span!("part", day = self.day, part = 1);
+        (self.part1)(self.input(parsed)).into_answer()
+    }
+
+    fn part2(&self, parsed: &Parsed) -> Result<String, AocError> {
+        let _span = span!("part", day = self.day, part = 2);
+        (self.part2)(self.input(parsed)).into_answer()
+    }
+
+    fn variants(&self, part: u8) -> Vec<&'static str> {
+        let solving = self.variants.iter().filter(|v| match part {
+            1 => v.part1.is_some(),
+            2 => v.part2.is_some(),
+            _ => false,
+        });
+        std::iter::once(DEFAULT_VARIANT)
+            .chain(solving.map(|v| v.name))
+            .collect()
+    }
+
+    fn variant_part(&self, variant: &str, part: u8, parsed: &Parsed) -> Result<String, AocError> {
+        if variant == DEFAULT_VARIANT {
+            return self.part(part, parsed);
+        }
+        let unknown = || AocError::UnknownVariant {
+            day: self.day,
+            part,
+            variant: variant.to_string(),
+        };
+        let found = self
+            .variants
+            .iter()
+            .find(|v| v.name == variant)
+            .ok_or_else(unknown)?;
+        let _span = span!("part", day = self.day, part = part, variant = variant);
+        match part {
+            1 => found.part1.ok_or_else(unknown)?(self.input(parsed)).into_answer(),
+            2 => found.part2.ok_or_else(unknown)?(self.input(parsed)).into_answer(),
+            x => Err(AocError::UnknownPart(x)),
+        }
+    }
+}
+
+/// Every solved day, ordered by day number
+pub fn solvers() -> Vec<Box<dyn Solver>> {
+    vec![
+        Box::new(crate::day1::solver()),
+        Box::new(crate::day2::solver()),
+        Box::new(crate::day3::solver()),
+        Box::new(crate::day4::solver()),
+        Box::new(crate::day5::solver()),
+        Box::new(crate::day6::solver()),
+        Box::new(crate::day7::solver()),
+        Box::new(crate::day8::solver()),
+        Box::new(crate::day9::solver()),
+        Box::new(crate::day10::solver()),
+        Box::new(crate::day11::solver()),
+        Box::new(crate::day12::solver()),
+        Box::new(crate::day13::solver()),
+        Box::new(crate::day14::solver()),
+        Box::new(crate::day15::solver()),
+        Box::new(crate::day16::solver()),
+    ]
+}
+
+pub fn solver(day: u8) -> Option<Box<dyn Solver>> {
+    solvers().into_iter().find(|s| s.day() == day)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::runner::DAYS;
+
+    #[test]
+    fn should_register_every_day() {
+        let days: Vec<u8> = solvers().iter().map(|s| s.day()).collect();
+        assert_eq!(days, DAYS.collect::<Vec<_>>());
+        assert!(solver(17).is_none());
+    }
+
+    #[test]
+    fn should_solve_examples() -> Result<(), AocError> {
+        for solver in solvers() {
+            assert!(!solver.examples().is_empty(), "Day {}", solver.day());
+            for example in solver.examples() {
+                let parsed = solver.parse(example.input)?;
+                if let Some(answer) = example.part1 {
+                    assert_eq!(solver.part1(&parsed)?, answer, "Day {}", solver.day());
+                }
+                if let Some(answer) = example.part2 {
+                    assert_eq!(solver.part2(&parsed)?, answer, "Day {}", solver.day());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn should_solve_examples_with_every_variant() -> Result<(), AocError> {
+        for solver in solvers() {
+            for example in solver.examples() {
+                let parsed = solver.parse(example.input)?;
+                for (part, answer) in [(1, example.part1), (2, example.part2)] {
+                    let Some(answer) = answer else {
+                        continue;
+                    };
+                    for variant in solver.variants(part) {
+                        let found = solver.variant_part(variant, part, &parsed)?;
+                        assert_eq!(found, answer, "Day {} {}", solver.day(), variant);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "cache")]
+    #[test]
+    fn should_solve_examples_from_cache() -> Result<(), AocError> {
+        let dir = std::env::temp_dir().join(format!("aoc-2021-solvers-{}", std::process::id()));
+        for solver in solvers() {
+            for example in solver.examples() {
+                let cache = Cache::new(&dir);
+                let _ = std::fs::remove_dir_all(&dir);
+                // Parsed then stored, then loaded back
+                for loaded in [false, true] {
+                    let parsed = solver.parse_cached(example.input, &cache)?;
+                    assert_eq!(parsed.1, loaded, "Day {}", solver.day());
+                    let parsed = parsed.0;
+                    if let Some(answer) = example.part1 {
+                        assert_eq!(solver.part1(&parsed)?, answer, "Day {}", solver.day());
+                    }
+                    if let Some(answer) = example.part2 {
+                        assert_eq!(solver.part2(&parsed)?, answer, "Day {}", solver.day());
+                    }
+                }
+            }
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+        Ok(())
+    }
+
+    #[cfg(feature = "cache")]
+    #[test]
+    fn should_parse_invalid_cache_entries_again() -> Result<(), AocError> {
+        use crate::grid::Grid;
+
+        let dir = std::env::temp_dir().join(format!("aoc-2021-invalid-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let cache = Cache::new(&dir);
+        let solver = solver(15).expect("Day 15 is solved");
+        let input = "19\n11";
+        // Risks start from 1, which expanding the cave relies on
+        let zero = Grid::from_vec(2, vec![1u8, 9, 1, 0]);
+        cache.store(15, input, &zero).expect("Failed to store");
+        let (parsed, loaded) = solver.parse_cached(input, &cache)?;
+        assert!(!loaded);
+        let part2 = crate::day15::part2(&crate::day15::parse(input)?);
+        assert_eq!(solver.part2(&parsed)?, part2.to_string());
+        // Stored again once parsed
+        assert!(solver.parse_cached(input, &cache)?.1);
+        let _ = std::fs::remove_dir_all(&dir);
+        Ok(())
+    }
+
+    #[test]
+    fn should_select_variants() -> Result<(), AocError> {
+        let solver = solver(7).expect("Day 7 is solved");
+        assert_eq!(solver.variants(1), vec![DEFAULT_VARIANT, "median", "naive"]);
+        assert_eq!(solver.variants(2), vec![DEFAULT_VARIANT, "mean", "naive"]);
+        let parsed = solver.parse("16,1,2,0,4,2,7,1,2,14")?;
+        assert_eq!(solver.variant_part("median", 1, &parsed)?, "37");
+        let unknown = |variant: &str, part| AocError::UnknownVariant {
+            day: 7,
+            part,
+            variant: variant.to_string(),
+        };
+        assert_eq!(
+            solver.variant_part("median", 2, &parsed),
+            Err(unknown("median", 2))
+        );
+        assert_eq!(
+            solver.variant_part("fastest", 1, &parsed),
+            Err(unknown("fastest", 1))
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+//! Single-pass solvers for the line-oriented days, reading from any `BufRead` so that inputs
+//! much larger than memory can be solved. Only the current line is kept around, plus whatever
+//! state the day needs: the covered points for day 5 and a score per incomplete line for day 10.
+
+use crate::error::AocError;
+use crate::input::normalize_line;
+use crate::runner::IntoAnswer;
+use std::io::BufRead;
+
+/// Calls `f` on each line of `input` normalized as by `input::normalize`, reusing the same
+/// buffer. Errors returned by `f` are relative to the line, as for `FromStr` impls.
+pub fn for_each_line(
+    day: u8,
+    mut input: impl BufRead,
+    mut f: impl FnMut(&str) -> Result<(), AocError>,
+) -> Result<(), AocError> {
+    let mut buffer = String::new();
+    let mut line = 1;
+    let mut blank_lines = 0;
+    loop {
+        buffer.clear();
+        match input.read_line(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(_) => (),
+            Err(e) => {
+                return Err(AocError::Io {
+                    day,
+                    line,
+                    kind: e.kind(),
+                })
+            }
+        }
+        let content = normalize_line(&buffer, line == 1);
+        if content.is_empty() {
+            // Only handed over once another line follows, as trailing blank lines are dropped
+            blank_lines += 1;
+        } else {
+            for blank in line - blank_lines..line {
+                f("").map_err(|e| e.at_line(blank))?;
+            }
+            blank_lines = 0;
+            f(content).map_err(|e| e.at_line(line))?;
+        }
+        line += 1;
+    }
+}
+
+/// Solves both parts of a day from a reader
+pub type Streamer = fn(&mut dyn BufRead) -> Result<[Result<String, AocError>; 2], AocError>;
+
+fn answers<A: IntoAnswer, B: IntoAnswer>((a, b): (A, B)) -> [Result<String, AocError>; 2] {
+    [a.into_answer(), b.into_answer()]
+}
+
+/// Days that can be streamed
+pub fn streamer(day: u8) -> Option<Streamer> {
+    match day {
+        1 => Some(|input| Ok(answers(crate::day1::stream(input)?))),
+        2 => Some(|input| Ok(answers(crate::day2::stream(input)?))),
+        5 => Some(|input| Ok(answers(crate::day5::stream(input)?))),
+        8 => Some(|input| Ok(answers(crate::day8::stream(input)?))),
+        10 => Some(|input| Ok(answers(crate::day10::stream(input)?))),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::error::ParseErrorKind;
+    use std::io::{self, BufReader, Read};
+
+    #[test]
+    fn should_stream_examples() -> Result<(), AocError> {
+        for day in crate::runner::DAYS {
+            let (streamer, solver) = match (streamer(day), crate::solver::solver(day)) {
+                (Some(streamer), Some(solver)) => (streamer, solver),
+                _ => continue,
+            };
+            for example in solver.examples() {
+                // Tiny buffer so that lines span several reads
+                let mut input = BufReader::with_capacity(3, example.input.as_bytes());
+                let [part1, part2] = streamer(&mut input)?;
+                assert_eq!(
+                    part1?.as_str(),
+                    example.part1.unwrap_or_default(),
+                    "Day {}",
+                    day
+                );
+                assert_eq!(
+                    part2?.as_str(),
+                    example.part2.unwrap_or_default(),
+                    "Day {}",
+                    day
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn should_locate_errors() {
+        let input = "199\r\n200\r\n2x8\r\n";
+        assert!(matches!(
+            crate::day1::stream(input.as_bytes()),
+            Err(AocError::Parse {
+                day: 1,
+                line: 3,
+                column: 1,
+                kind: ParseErrorKind::Int(_),
+            })
+        ));
+
+        // Blank lines are only fine at the end
+        assert!(crate::day1::stream("1\n\n2\n\n".as_bytes()).is_err());
+        assert_eq!(crate::day1::stream("1\n2\n\n \n".as_bytes()), Ok((1, 0)));
+        assert!(matches!(
+            crate::day1::stream("1\n \n2".as_bytes()),
+            Err(AocError::Parse { line: 2, .. })
+        ));
+
+        struct Broken;
+        impl Read for Broken {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::ErrorKind::BrokenPipe.into())
+            }
+        }
+        assert_eq!(
+            crate::day2::stream(BufReader::new(Broken)).err(),
+            Some(AocError::Io {
+                day: 2,
+                line: 1,
+                kind: io::ErrorKind::BrokenPipe,
+            })
+        );
+    }
+}
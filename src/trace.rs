@@ -0,0 +1,113 @@
+//! Optional instrumentation with `tracing`, enabled by the `tracing` feature. `Solution` opens a
+//! `parse` or `part` span around each step and solvers record their key intermediate values as
+//! debug events. Without the feature, the macros expand to nothing.
+
+#[cfg(feature = "tracing")]
+macro_rules! event {
+    ($($arg:tt)*) => {
+        tracing::debug!($($arg)*)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! event {
+    ($($arg:tt)*) => {};
+}
+
+/// Opens a debug span, closed when the returned guard is dropped
+#[cfg(feature = "tracing")]
+macro_rules! span {
+    ($($arg:tt)*) => {
+        tracing::debug_span!($($arg)*).entered()
+    };
+}
+
+#[cfg(all(feature = "std", not(feature = "tracing")))]
+macro_rules! span {
+    ($($arg:tt)*) => {
+        $crate::trace::NoSpan
+    };
+}
+
+pub(crate) use event;
+// Only `Solution` opens spans
+#[cfg(feature = "std")]
+pub(crate) use span;
+
+#[cfg(all(feature = "std", not(feature = "tracing")))]
+pub(crate) struct NoSpan;
+
+#[cfg(test)]
+#[cfg(feature = "tracing")]
+pub mod tests {
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    /// Keeps the fields of every event and the names of the spans
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    struct Fields<'a>(&'a mut String);
+
+    impl Visit for Fields<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            *self.0 += &format!("{}={:?} ", field.name(), value);
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut line = format!("span {} ", span.metadata().name());
+            span.record(&mut Fields(&mut line));
+            self.0.lock().unwrap().push(line);
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut line = String::new();
+            event.record(&mut Fields(&mut line));
+            self.0.lock().unwrap().push(line);
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    fn record(day: u8, input: &str) -> Vec<String> {
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            crate::runner::run_day(day, input, &[1, 2]).expect("Example should run");
+        });
+        let lines = recorder.0.lock().unwrap().clone();
+        lines
+    }
+
+    #[test]
+    fn should_trace_solvers() {
+        let examples = |day| crate::solver::solver(day).unwrap().examples()[0].input;
+        let lines = record(4, examples(4));
+        assert_eq!(lines[0], "span parse day=4 ");
+        assert!(lines.contains(&"span part day=4 part=1 ".to_string()));
+        assert!(lines
+            .iter()
+            .any(|l| l.contains("board wins") && l.contains("board=2 ")));
+
+        let lines = record(3, examples(3));
+        assert!(lines.iter().any(|l| l.contains("bit=4 candidates=7")));
+        assert_eq!(record(11, examples(11)).len(), 3 + 100 + 195);
+        assert!(record(14, examples(14))
+            .iter()
+            .any(|l| l.contains("length=97")));
+    }
+}
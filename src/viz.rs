@@ -0,0 +1,165 @@
+//! Frames showing the intermediate states of the simulation days, for debugging and demos.
+//! They are rendered as ASCII art or as binary PGM/PPM images, one file per frame, which most
+//! tools can turn into a video (e.g. `ffmpeg -i frame%04d.ppm out.mp4`).
+
+use crate::error::AocError;
+use crate::geom::Point2;
+use crate::grid::Grid;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+pub type Rgb = [u8; 3];
+
+/// Darkest to brightest
+const ASCII_RAMP: &[u8] = b" .:-=+*#%@";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Frame {
+    Gray(Grid<u8>),
+    Color(Grid<Rgb>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Ascii,
+    /// PGM for gray frames, PPM for color ones
+    Pnm,
+}
+
+pub trait Visualize {
+    /// Every state worth showing, in order
+    fn frames(&self) -> Vec<Frame>;
+}
+
+impl Frame {
+    pub fn width(&self) -> usize {
+        match self {
+            Frame::Gray(grid) => grid.width(),
+            Frame::Color(grid) => grid.width(),
+        }
+    }
+
+    pub fn height(&self) -> usize {
+        match self {
+            Frame::Gray(grid) => grid.height(),
+            Frame::Color(grid) => grid.height(),
+        }
+    }
+
+    /// Brightness of every pixel
+    pub fn to_gray(&self) -> Grid<u8> {
+        match self {
+            Frame::Gray(grid) => grid.clone(),
+            Frame::Color(grid) => grid.map(|&[r, g, b]| {
+                ((r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000) as u8
+            }),
+        }
+    }
+
+    /// One char per pixel, brighter pixels getting denser chars
+    pub fn to_ascii(&self) -> String {
+        let last = ASCII_RAMP.len() - 1;
+        self.to_gray()
+            .map(|&level| ASCII_RAMP[level as usize * last / 255] as char)
+            .to_string()
+    }
+
+    /// Each pixel becomes a `factor` by `factor` square, as puzzle grids make tiny images
+    pub fn scaled(&self, factor: usize) -> Frame {
+        fn scale<T: Clone>(grid: &Grid<T>, factor: usize) -> Grid<T> {
+            Grid::from_fn(grid.width() * factor, grid.height() * factor, |pos| {
+                grid[Point2::new(pos.x / factor, pos.y / factor)].clone()
+            })
+        }
+        match self {
+            Frame::Gray(grid) => Frame::Gray(scale(grid, factor)),
+            Frame::Color(grid) => Frame::Color(scale(grid, factor)),
+        }
+    }
+
+    pub fn extension(&self, format: Format) -> &'static str {
+        match (format, self) {
+            (Format::Ascii, _) => "txt",
+            (Format::Pnm, Frame::Gray(_)) => "pgm",
+            (Format::Pnm, Frame::Color(_)) => "ppm",
+        }
+    }
+
+    pub fn write(&self, out: &mut impl Write, format: Format) -> io::Result<()> {
+        match (format, self) {
+            (Format::Ascii, _) => writeln!(out, "{}", self.to_ascii()),
+            (Format::Pnm, Frame::Gray(grid)) => {
+                write!(out, "P5\n{} {}\n255\n", grid.width(), grid.height())?;
+                out.write_all(&grid.iter().copied().collect::<Vec<_>>())
+            }
+            (Format::Pnm, Frame::Color(grid)) => {
+                write!(out, "P6\n{} {}\n255\n", grid.width(), grid.height())?;
+                out.write_all(&grid.iter().flatten().copied().collect::<Vec<_>>())
+            }
+        }
+    }
+}
+
+/// Writes frames to `dir` as `frame0000.txt`, `frame0001.txt`... (or `.pgm`/`.ppm`),
+/// creating it if needed
+pub fn write_frames(frames: &[Frame], dir: &Path, format: Format) -> io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    for (idx, frame) in frames.iter().enumerate() {
+        let path = dir.join(format!("frame{:04}.{}", idx, frame.extension(format)));
+        let mut out = BufWriter::new(File::create(path)?);
+        frame.write(&mut out, format)?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+/// Parses an input, once normalized, and renders its frames
+pub type Visualizer = fn(&str) -> Result<Vec<Frame>, AocError>;
+
+/// Days that can be visualized
+pub fn visualizer(day: u8) -> Option<Visualizer> {
+    use crate::{day11, day13, day15, day9};
+    match day {
+        9 => Some(|input| Ok(day9::Basins(&day9::parse(input)?).frames())),
+        11 => Some(|input| Ok(day11::Flashes(&day11::parse(input)?).frames())),
+        13 => Some(|input| Ok(day13::parse(input)?.frames())),
+        15 => Some(|input| Ok(day15::PathSearch(&day15::parse(input)?).frames())),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn should_render_frames() -> io::Result<()> {
+        let frame = Frame::Gray(Grid::from_vec(3, vec![0, 128, 255, 255, 0, 0]));
+        assert_eq!(frame.to_ascii(), " =@\n@  ");
+
+        let mut pgm = Vec::new();
+        frame.write(&mut pgm, Format::Pnm)?;
+        assert_eq!(pgm, b"P5\n3 2\n255\n\x00\x80\xff\xff\x00\x00");
+
+        let color = Frame::Color(Grid::from_vec(1, vec![[255, 0, 0]])).scaled(2);
+        let mut ppm = Vec::new();
+        color.write(&mut ppm, Format::Pnm)?;
+        assert_eq!(ppm.len(), "P6\n2 2\n255\n".len() + 4 * 3);
+        assert_eq!(color.to_gray()[Point2::new(1, 1)], 76);
+        Ok(())
+    }
+
+    #[test]
+    fn should_visualize_examples() -> Result<(), AocError> {
+        for day in crate::runner::DAYS {
+            let (visualizer, solver) = match (visualizer(day), crate::solver::solver(day)) {
+                (Some(visualizer), Some(solver)) => (visualizer, solver),
+                _ => continue,
+            };
+            let frames = visualizer(solver.examples()[0].input)?;
+            assert!(frames.len() > 1, "Day {}", day);
+        }
+        Ok(())
+    }
+}